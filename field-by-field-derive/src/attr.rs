@@ -0,0 +1,209 @@
+//! Parsing for `#[field_by_field(...)]` attributes
+
+use syn;
+
+/// Options that can be set on a single field
+///
+/// e.g. `#[field_by_field(redact)] password: String`
+#[derive(Debug, Default)]
+pub struct FieldOpts {
+    /// Never print the value of this field, only a short hash of it
+    pub redact: bool,
+    /// Print the value of this field with its `Display` impl
+    pub display: bool,
+    /// Print the value of this field with this `fn(&T, &mut Formatter) -> fmt::Result`
+    pub debug_with: Option<syn::Path>,
+    /// Compare the result of this `fn(&T) -> U` instead of the field itself
+    pub normalize: Option<syn::Path>,
+    /// Compare this sequence as a multiset, ignoring the order of its elements
+    pub unordered: bool,
+    /// Report this field's own differing fields as if they were ours
+    pub flatten: bool,
+    /// Compare this field with its own `EqualFieldByField` impl, under our path
+    pub nested: bool,
+    /// Diff this sequence by aligning equal elements, like a text diff
+    pub align: bool,
+    /// The field looks like a sequence, so it can be compared unordered at runtime, or
+    /// element by element if it's also `nested`
+    pub sequence: bool,
+    /// The field looks like a map, so it can be compared as a subset at runtime
+    pub map: bool,
+    /// Don't compare this field at all
+    ///
+    /// Set automatically for marker fields like `PhantomData<T>`.
+    pub skip: bool,
+}
+
+impl FieldOpts {
+    pub fn from_field(field: &syn::Field) -> FieldOpts {
+        let mut opts = FieldOpts::default();
+        let mut include = false;
+        for item in field_by_field_items(&field.attrs) {
+            match *item {
+                syn::MetaItem::Word(ref name) if name == "skip" => opts.skip = true,
+                syn::MetaItem::Word(ref name) if name == "include" => include = true,
+                syn::MetaItem::Word(ref name) if name == "redact" => opts.redact = true,
+                syn::MetaItem::Word(ref name) if name == "display" => opts.display = true,
+                syn::MetaItem::Word(ref name) if name == "unordered" => opts.unordered = true,
+                syn::MetaItem::Word(ref name) if name == "flatten" => opts.flatten = true,
+                syn::MetaItem::Word(ref name) if name == "nested" => opts.nested = true,
+                syn::MetaItem::Word(ref name) if name == "align" => opts.align = true,
+                syn::MetaItem::NameValue(ref name, ref value) if name == "debug_with" =>
+                    opts.debug_with = Some(parse_path_lit(name, value)),
+                syn::MetaItem::NameValue(ref name, ref value) if name == "normalize" =>
+                    opts.normalize = Some(parse_path_lit(name, value)),
+                ref other => panic!("Unknown field_by_field field attribute: {:?}", other),
+            }
+        }
+        let renderers = [opts.redact, opts.display, opts.debug_with.is_some()];
+        if renderers.iter().filter(|set| **set).count() > 1 {
+            panic!("Only one of `redact`, `display` and `debug_with` can be used on field {:?}",
+                   field.ident);
+        }
+        let customized = renderers.iter().any(|set| *set) || opts.normalize.is_some();
+        let structural = [("unordered", opts.unordered),
+                          ("flatten", opts.flatten),
+                          ("nested", opts.nested),
+                          ("align", opts.align)];
+        // `unordered` and `nested` together pair up the elements of a sequence of structs
+        let paired = opts.unordered && opts.nested && !opts.flatten && !opts.align;
+        for &(attr, set) in &structural {
+            let others = structural.iter().any(|&(other, other_set)| other != attr && other_set);
+            if set && (customized || (others && !paired)) {
+                panic!("`{}` can't be combined with other field_by_field attributes on \
+                        field {:?}", attr, field.ident);
+            }
+        }
+        if opts.skip && include {
+            panic!("`skip` and `include` can't both be used on field {:?}", field.ident);
+        }
+        if !include && is_marker_type(&field.ty) {
+            opts.skip = true;
+        }
+        opts.sequence = !customized && is_sequence_type(&field.ty);
+        opts.map = !customized && is_map_type(&field.ty);
+        opts
+    }
+}
+
+/// True for types that never hold any data worth comparing
+///
+/// This is necessarily a guess based on how the type is spelled:
+/// `PhantomData<T>`, `PhantomPinned`, `()` and `[T; 0]`.
+fn is_marker_type(ty: &syn::Ty) -> bool {
+    match *ty {
+        syn::Ty::Path(None, ref path) => path.segments.last()
+            .is_some_and(|seg| seg.ident == "PhantomData" || seg.ident == "PhantomPinned"),
+        syn::Ty::Tup(ref types) => types.is_empty(),
+        syn::Ty::Array(_, syn::ConstExpr::Lit(syn::Lit::Int(0, _))) => true,
+        syn::Ty::Paren(ref ty) => is_marker_type(ty),
+        _ => false,
+    }
+}
+
+/// True for types that can be compared with `unordered_diff`
+///
+/// Like `is_marker_type` this goes by spelling: `Vec<T>`, `VecDeque<T>`,
+/// `HashSet<T>`, `BTreeSet<T>`, `[T; N]` and references to those or to `[T]`.
+fn is_sequence_type(ty: &syn::Ty) -> bool {
+    match *ty {
+        syn::Ty::Path(None, ref path) => path.segments.last()
+            .is_some_and(|seg| ["Vec", "VecDeque", "HashSet", "BTreeSet"]
+                .iter()
+                .any(|name| seg.ident == *name)),
+        syn::Ty::Array(..) | syn::Ty::Slice(_) => true,
+        syn::Ty::Rptr(_, ref ty) => is_sequence_type(&ty.ty),
+        syn::Ty::Paren(ref ty) => is_sequence_type(ty),
+        _ => false,
+    }
+}
+
+/// True for `HashMap<K, V>` and `BTreeMap<K, V>`, and references to them
+fn is_map_type(ty: &syn::Ty) -> bool {
+    match *ty {
+        syn::Ty::Path(None, ref path) => path.segments.last()
+            .is_some_and(|seg| seg.ident == "HashMap" || seg.ident == "BTreeMap"),
+        syn::Ty::Rptr(_, ref ty) => is_map_type(&ty.ty),
+        syn::Ty::Paren(ref ty) => is_map_type(ty),
+        _ => false,
+    }
+}
+
+/// Options that can be set on the struct or enum itself
+///
+/// e.g. `#[field_by_field(transparent)] struct UserId(u64);`
+#[derive(Debug, Default)]
+pub struct ContainerOpts {
+    /// Report differences in the only field without adding its name to the path
+    pub transparent: bool,
+    /// Also implement `PartialEq` in terms of `fields_not_equal`
+    pub derive_partial_eq: bool,
+    /// Also generate a `<Name>Expectation` struct with a matcher per field
+    pub expectation: bool,
+}
+
+impl ContainerOpts {
+    pub fn from_input(input: &syn::MacroInput) -> ContainerOpts {
+        let mut opts = ContainerOpts::default();
+        for item in field_by_field_items(&input.attrs) {
+            match *item {
+                syn::MetaItem::Word(ref name) if name == "transparent" => opts.transparent = true,
+                syn::MetaItem::Word(ref name) if name == "derive_partial_eq" =>
+                    opts.derive_partial_eq = true,
+                syn::MetaItem::Word(ref name) if name == "expectation" => opts.expectation = true,
+                ref other => panic!("Unknown field_by_field container attribute: {:?}", other),
+            }
+        }
+        if opts.transparent {
+            match input.body {
+                syn::Body::Struct(ref data) if data.fields().len() == 1 => {}
+                _ => panic!("`transparent` can only be used on structs with exactly one field, \
+                             not {}", input.ident),
+            }
+        }
+        if opts.expectation {
+            match input.body {
+                syn::Body::Struct(syn::VariantData::Struct(_)) => {}
+                _ => panic!("`expectation` can only be used on structs with named fields, not {}",
+                            input.ident),
+            }
+            if !input.generics.lifetimes.is_empty() || !input.generics.ty_params.is_empty() {
+                panic!("`expectation` can't be used on generic types like {}", input.ident);
+            }
+        }
+        opts
+    }
+}
+
+/// Parse the string in e.g. `normalize = "path::to::fn"` as a path
+fn parse_path_lit(name: &syn::Ident, value: &syn::Lit) -> syn::Path {
+    match *value {
+        syn::Lit::Str(ref path, _) => syn::parse_path(path)
+            .unwrap_or_else(|e| panic!("Invalid path for `{}`: {}", name, e)),
+        ref other => panic!("`{}` should be a string containing a path, not {:?}", name, other),
+    }
+}
+
+/// All the items inside of every `#[field_by_field(...)]` attribute
+fn field_by_field_items(attrs: &[syn::Attribute]) -> Vec<&syn::MetaItem> {
+    let mut items = Vec::new();
+    for attr in attrs {
+        if attr.name() != "field_by_field" {
+            continue;
+        }
+        match attr.value {
+            syn::MetaItem::List(_, ref nested) => {
+                for item in nested {
+                    match *item {
+                        syn::NestedMetaItem::MetaItem(ref meta) => items.push(meta),
+                        syn::NestedMetaItem::Literal(ref lit) =>
+                            panic!("Unexpected literal in field_by_field attribute: {:?}", lit),
+                    }
+                }
+            }
+            ref other => panic!("field_by_field attributes must look like \
+                                 `#[field_by_field(...)]`, not {:?}", other),
+        }
+    }
+    items
+}
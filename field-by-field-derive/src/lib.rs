@@ -1,6 +1,56 @@
-/// Custom-derive providing macros for the field-by-field Traits
-///
-/// See the tests/derive.rs file for examples of use.
+//! Custom-derive providing macros for the field-by-field Traits
+//!
+//! See the tests/derive.rs file for examples of use.
+//!
+//! Fields are shown with their `Debug` impl in failure messages. Fields whose
+//! types don't implement `Debug` are still compared, and are shown as
+//! `<value without Debug, type = path::to::Type>`.
+//!
+//! Fields can be annotated with `#[field_by_field(...)]` attributes:
+//!
+//! * `redact`: report that the field differs, but only print a short hash of
+//!   its value instead of its `Debug` representation.
+//! * `display`: print the field using its `Display` impl instead of `Debug`.
+//! * `debug_with = "path::to::fn"`: print the field by calling
+//!   `fn(&T, &mut fmt::Formatter) -> fmt::Result` instead of using `Debug`.
+//! * `normalize = "path::to::fn"`: compare the results of calling
+//!   `fn(&T) -> U` on both sides instead of the fields themselves. `U` must be
+//!   `PartialEq + Debug`.
+//! * `unordered`: compare a `Vec`, slice or anything else with an `.iter()`
+//!   method as a multiset, and report the elements that are only on one side.
+//! * `flatten`: compare a field whose type is itself `EqualFieldByField` using
+//!   its `fields_not_equal`, and report its fields as if they were declared on
+//!   the containing type.
+//! * `nested`: compare a field whose type is itself `EqualFieldByField` using
+//!   its `fields_not_equal`, reporting its fields under this one, like
+//!   `address.zip`. The field's type doesn't need to implement `PartialEq`.
+//! * `unordered, nested` together: compare a sequence of `EqualFieldByField +
+//!   Debug` elements as a multiset, pairing up elements so that as few fields
+//!   as possible differ, and report each pair's fields like `lines[2].qty`.
+//! * `align`: compare a sequence of `EqualFieldByField + Debug` elements like
+//!   a text diff, so that an inserted or removed element is reported once
+//!   instead of shifting every element after it.
+//! * `skip`: don't compare the field at all. This is the default for marker
+//!   fields like `PhantomData<T>`, `PhantomPinned`, `()` and `[T; 0]`, so they
+//!   don't need to be `PartialEq`.
+//! * `include`: compare a marker field anyway.
+//!
+//! Structs can be annotated with `#[field_by_field(...)]` attributes:
+//!
+//! * `transparent`: on a struct with exactly one field, report differences in
+//!   that field without adding its name (or `.0`) to the path.
+//!
+//! Structs and enums can be annotated with:
+//!
+//! * `derive_partial_eq`: also implement `PartialEq`, as "`fields_not_equal`
+//!   is empty", so that `==` follows exactly the same rules as the field by
+//!   field comparison.
+//!
+//! Non-generic structs with named fields can be annotated with:
+//!
+//! * `expectation`: also generate a `<Name>Expectation` struct with an
+//!   `Expect<T>` matcher for every compared field, defaulting to `any()`, and
+//!   `mismatches` and `assert_matches` methods to check a value against it.
 
 extern crate field_by_field;
 extern crate proc_macro;
@@ -10,8 +60,11 @@ extern crate syn;
 
 use proc_macro::TokenStream;
 
+mod attr;
+
+use attr::{ContainerOpts, FieldOpts};
 
-#[proc_macro_derive(FieldByField)]
+#[proc_macro_derive(FieldByField, attributes(field_by_field))]
 pub fn derive(input: TokenStream) -> TokenStream {
     expand_derive(input, build_trait_field_by_field)
 }
@@ -23,78 +76,567 @@ fn expand_derive(input: TokenStream, f: fn(syn::MacroInput) -> quote::Tokens) ->
 
 fn build_trait_field_by_field(input: syn::MacroInput) -> quote::Tokens {
     let name = &input.ident;
+    let opts = ContainerOpts::from_input(&input);
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    let fn_assert_equal_field_by_field = build_fn_assert_equal_field_by_field(&name);
+    let fn_debug_dump = build_fn_debug_dump(name, &input.body);
+    let fn_assert_equal_field_by_field = build_fn_assert_equal_field_by_field(name, &input.body);
 
     let fn_fields_not_equal = match input.body {
-        syn::Body::Struct(ref data) => build_fn_fields_not_equal(&data.fields()),
-        syn::Body::Enum(ref data) => build_fn_fields_not_equal_enum(&name, &data)
+        syn::Body::Struct(ref data) => build_fn_fields_not_equal(data.fields(), &opts),
+        syn::Body::Enum(ref data) => build_fn_fields_not_equal_enum(name, data, false)
     };
-    quote! {
+    let fn_fields_not_equal_with = match input.body {
+        syn::Body::Struct(ref data) => build_fn_fields_not_equal_with(data.fields(), &opts),
+        syn::Body::Enum(ref data) => build_fn_fields_not_equal_enum(name, data, true),
+    };
+    let impl_field_by_field = quote! {
         impl #impl_generics ::field_by_field::EqualFieldByField
         for #name #ty_generics #where_clause {
 
             #fn_fields_not_equal
 
+            #fn_fields_not_equal_with
+
+            #fn_debug_dump
+
             #fn_assert_equal_field_by_field
         }
+    };
+
+    let impl_partial_eq = if opts.derive_partial_eq {
+        build_impl_partial_eq(&input)
+    } else {
+        quote! {}
+    };
+    let expectation = if opts.expectation {
+        build_expectation(&input)
+    } else {
+        quote! {}
+    };
+    quote! {
+        #impl_field_by_field
+        #impl_partial_eq
+        #expectation
     }
 }
 
-/// Build a function that compares all the items in a simple struct
+/// Build a `PartialEq` impl that agrees with `fields_not_equal`
 ///
-/// This emits just a long list of `if self.name != other.name {
-/// vec.push(UnequalField); }` tokens.
-fn build_fn_fields_not_equal(fields: &[syn::Field]) -> quote::Tokens {
-    let find_unequal_fields = fields.iter()
-        .map(|f| {
-            let f_name = &f.ident;
-            let f_str = f_name.as_ref().map(|v| v.to_string())
-                .expect(&format!("Couldn't convert field to str: {:?}", f));
-            quote! {
-                if self.#f_name != other.#f_name {
-                    list.push(::field_by_field::UnequalField {
-                        field_name: #f_str.to_string(),
-                        actually: Box::new(self.#f_name.clone()),
-                        expected: Box::new(other.#f_name.clone()),
-                    });
+/// This is slower than a plain `#[derive(PartialEq)]`, since it collects every
+/// differing field, but it can't get out of sync with skipped, normalized or
+/// nested fields.
+fn build_impl_partial_eq(input: &syn::MacroInput) -> quote::Tokens {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::std::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                ::field_by_field::EqualFieldByField::fields_not_equal(self, other).is_empty()
+            }
+        }
+    }
+}
+
+/// Build `<Name>Expectation`, which holds an `Expect<T>` for every compared field
+///
+/// Every field defaults to `any()`, so an expectation only needs to mention
+/// the fields it cares about: `OrderExpectation { id: matches_regex("^ord_"),
+/// ..Default::default() }`.
+fn build_expectation(input: &syn::MacroInput) -> quote::Tokens {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let expectation = syn::Ident::new(format!("{}Expectation", name));
+    let all_fields = match input.body {
+        syn::Body::Struct(ref data) => data.fields(),
+        syn::Body::Enum(_) => unreachable!("ContainerOpts only allows `expectation` on structs"),
+    };
+    let fields: Vec<_> = all_fields.iter()
+        .filter(|f| !FieldOpts::from_field(f).skip)
+        .collect();
+
+    let declarations = fields.iter().map(|f| {
+        let (f_vis, f_name, f_ty) = (&f.vis, &f.ident, &f.ty);
+        quote! { #f_vis #f_name: ::field_by_field::Expect<#f_ty> }
+    });
+    let defaults = fields.iter().map(|f| {
+        let f_name = &f.ident;
+        quote! { #f_name: ::field_by_field::any() }
+    });
+    let checks = fields.iter().map(|f| {
+        let f_name = &f.ident;
+        let f_str = f_name.as_ref().map(|ident| ident.to_string()).unwrap_or_default();
+        let actually_value = build_boxed_value(&quote! { &actual.#f_name },
+                                               &FieldOpts::from_field(f));
+        quote! {
+            if !self.#f_name.matches(&actual.#f_name) {
+                list.push(::field_by_field::UnequalField {
+                    field_name: #f_str.into(),
+                    actually: #actually_value,
+                    expected: Box::new(self.#f_name.description()),
+                    kind: ::field_by_field::DiffKind::Unmatched,
+                });
+            }
+        }
+    });
+    let actually_dump = build_debug_dump(name, &input.body, &quote! { actual });
+    let doc = format!("Matchers for the fields of a `{}`, generated by `FieldByField`", name);
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #expectation {
+            #(#declarations,)*
+        }
+
+        impl ::std::default::Default for #expectation {
+            fn default() -> Self {
+                #expectation {
+                    #(#defaults,)*
                 }
             }
-        });
+        }
+
+        impl #expectation {
+            /// The fields of `actual` that don't satisfy their matcher
+            #[allow(dead_code)]
+            #vis fn mismatches(&self, actual: &#name) -> Vec<::field_by_field::UnequalField> {
+                #![allow(unused_mut)]
+                let mut list: Vec<::field_by_field::UnequalField> = Vec::new();
+                #(#checks)*
+                list
+            }
+
+            /// Panics if any field of `actual` doesn't satisfy its matcher
+            #[allow(dead_code)]
+            #vis fn assert_matches(&self, actual: &#name) {
+                let diff = ::field_by_field::Diff::from_fields(self.mismatches(actual));
+                if !diff.is_empty() {
+                    panic!("{}", ::field_by_field::mismatch_message(&diff, &#actually_dump));
+                }
+            }
+        }
+    }
+}
+
+/// Build a function that compares all the items in a simple struct
+///
+/// This emits just a long list of `if self.name != other.name {
+/// vec.push(UnequalField); }` tokens. Tuple struct fields are named by their
+/// index, like `0`.
+fn build_fn_fields_not_equal(fields: &[syn::Field], opts: &ContainerOpts) -> quote::Tokens {
+    let find_unequal_fields = build_field_comparisons(fields, opts, false);
 
     quote! {
         fn fields_not_equal(&self, other: &Self)
         -> Vec<::field_by_field::UnequalField> {
+            // This is never modified for unit structs
+            #![allow(unused_mut)]
+            let mut list: Vec<::field_by_field::UnequalField> = Vec::new();
+
+            #(#find_unequal_fields)*
+
+            list
+        }
+    }
+}
+
+/// Build `fields_not_equal_with`, which follows a runtime `CompareOptions`
+///
+/// This is the same as `fields_not_equal`, except that sequence fields are
+/// compared unordered if the options say so, `Approximate` fields are compared
+/// with their tolerance, and the options are passed down to `flatten` and
+/// `nested` fields.
+fn build_fn_fields_not_equal_with(fields: &[syn::Field], opts: &ContainerOpts) -> quote::Tokens {
+    let find_unequal_fields = build_field_comparisons(fields, opts, true);
+
+    quote! {
+        fn fields_not_equal_with(&self, other: &Self, opts: &::field_by_field::CompareOptions)
+        -> Vec<::field_by_field::UnequalField> {
+            #![allow(unused_mut, unused_variables)]
             let mut list: Vec<::field_by_field::UnequalField> = Vec::new();
 
             #(#find_unequal_fields)*
 
+            list.retain(|field| opts.reports(field));
             list
         }
     }
 }
 
-/// Build a function that compares enum variants and their nested fields
+fn build_field_comparisons(fields: &[syn::Field], opts: &ContainerOpts, with_opts: bool)
+-> Vec<quote::Tokens> {
+    fields.iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let f_name = f.ident.clone().unwrap_or_else(|| syn::Ident::from(i));
+            let f_str = if opts.transparent { String::new() } else { f_name.to_string() };
+            build_field_comparison(&f_str,
+                                   &quote! { &self.#f_name },
+                                   &quote! { &other.#f_name },
+                                   &FieldOpts::from_field(f),
+                                   with_opts)
+        })
+        .collect()
+}
+
+/// Build the check for a single field, pushing an UnequalField if it differs
+///
+/// `actually` and `expected` must be expressions that evaluate to references
+/// to the field on each side. If `with_opts` is set, the comparison can also
+/// use the `opts: &CompareOptions` that `fields_not_equal_with` is given.
+fn build_field_comparison(field_name: &str,
+                          actually: &quote::Tokens,
+                          expected: &quote::Tokens,
+                          opts: &FieldOpts,
+                          with_opts: bool)
+-> quote::Tokens {
+    if opts.skip {
+        return quote! {};
+    }
+
+    if opts.flatten && with_opts {
+        return quote! {
+            list.extend(::field_by_field::EqualFieldByField::fields_not_equal_with(
+                #actually, #expected, opts));
+        };
+    }
+
+    if opts.flatten {
+        return quote! {
+            list.extend(::field_by_field::EqualFieldByField::fields_not_equal(#actually,
+                                                                              #expected));
+        };
+    }
+
+    // In subset mode, elements that are only in actual don't count
+    let keep = if with_opts {
+        quote! { |field: &::field_by_field::UnequalField| {
+            !opts.is_subset() || field.kind != ::field_by_field::DiffKind::OnlyInActual
+        }}
+    } else {
+        quote! { |_: &::field_by_field::UnequalField| true }
+    };
+
+    if opts.align {
+        return quote! {
+            list.extend(::field_by_field::aligned_diff((#actually).iter(), (#expected).iter())
+                        .into_iter()
+                        .filter(#keep)
+                        .map(|field| field.nested_under(#field_name)));
+        };
+    }
+
+    if opts.unordered && opts.nested {
+        return quote! {
+            list.extend(::field_by_field::paired_unordered_diff((#actually).iter(),
+                                                                (#expected).iter())
+                        .into_iter()
+                        .filter(#keep)
+                        .map(|field| field.nested_under(#field_name)));
+        };
+    }
+
+    if opts.nested && opts.sequence && with_opts {
+        return quote! {
+            {
+                let __opts = opts.nested(#field_name);
+                let __fields = if opts.is_unordered(#field_name) {
+                    ::field_by_field::paired_unordered_diff((#actually).iter(),
+                                                            (#expected).iter())
+                } else {
+                    ::field_by_field::indexed_diff(
+                        (#actually).iter(), (#expected).iter(),
+                        |__actual, __expected, __index| {
+                            ::field_by_field::EqualFieldByField::fields_not_equal_with(
+                                __actual, __expected, &__opts.nested(__index))
+                        })
+                };
+                list.extend(__fields.into_iter()
+                            .filter(#keep)
+                            .map(|field| field.nested_under(#field_name)));
+            }
+        };
+    }
+
+    if opts.nested && opts.sequence {
+        return quote! {
+            list.extend(::field_by_field::indexed_diff(
+                            (#actually).iter(), (#expected).iter(),
+                            |__actual, __expected, _| {
+                                ::field_by_field::EqualFieldByField::fields_not_equal(
+                                    __actual, __expected)
+                            })
+                        .into_iter()
+                        .map(|field| field.nested_under(#field_name)));
+        };
+    }
+
+    if opts.nested && with_opts {
+        return quote! {
+            list.extend(::field_by_field::EqualFieldByField::fields_not_equal_with(
+                            #actually, #expected, &opts.nested(#field_name))
+                        .into_iter()
+                        .map(|field| field.nested_under(#field_name)));
+        };
+    }
+
+    if opts.nested {
+        return quote! {
+            list.extend(::field_by_field::EqualFieldByField::fields_not_equal(#actually, #expected)
+                        .into_iter()
+                        .map(|field| field.nested_under(#field_name)));
+        };
+    }
+
+    if opts.unordered && with_opts {
+        let unordered = build_unordered_comparison(field_name, actually, expected);
+        let subset = build_subset_sequence_comparison(field_name, actually, expected);
+        return quote! {
+            if opts.is_subset() {
+                #subset
+            } else {
+                #unordered
+            }
+        };
+    }
+
+    if opts.unordered {
+        return build_unordered_comparison(field_name, actually, expected);
+    }
+
+    let actually_value = build_boxed_value(actually, opts);
+    let expected_value = build_boxed_value(expected, opts);
+
+    if let Some(ref normalize) = opts.normalize {
+        let (actually_norm, expected_norm) = if opts.redact {
+            (quote! { &::field_by_field::Redacted::new(&__actually_norm) },
+             quote! { &::field_by_field::Redacted::new(&__expected_norm) })
+        } else {
+            (quote! { &__actually_norm }, quote! { &__expected_norm })
+        };
+        return quote! {
+            {
+                let __actually_norm = #normalize(#actually);
+                let __expected_norm = #normalize(#expected);
+                if __actually_norm != __expected_norm {
+                    list.push(::field_by_field::UnequalField {
+                        field_name: #field_name.into(),
+                        actually: Box::new(::field_by_field::Rendered::normalized(
+                            #actually_norm, &#actually_value)),
+                        expected: Box::new(::field_by_field::Rendered::normalized(
+                            #expected_norm, &#expected_value)),
+                        kind: ::field_by_field::DiffKind::Changed,
+                    });
+                }
+            }
+        };
+    }
+
+    let differs = if with_opts {
+        quote! {
+            match opts.tolerance_for(#field_name) {
+                Some(__tolerance) => {
+                    #[allow(unused_imports)]
+                    use ::field_by_field::{ViaApproximate, ViaExact};
+                    (&::field_by_field::MaybeApproximate(#actually, #expected))
+                        .differs_within(__tolerance)
+                }
+                None => #actually != #expected,
+            }
+        }
+    } else {
+        quote! { #actually != #expected }
+    };
+    let comparison = quote! {
+        if #differs {
+            list.push(::field_by_field::UnequalField {
+                field_name: #field_name.into(),
+                actually: #actually_value,
+                expected: #expected_value,
+                kind: ::field_by_field::DiffKind::Changed,
+            });
+        }
+    };
+
+    if opts.sequence && with_opts {
+        let unordered = build_unordered_comparison(field_name, actually, expected);
+        let subset = build_subset_sequence_comparison(field_name, actually, expected);
+        quote! {
+            if opts.is_subset() {
+                #subset
+            } else if opts.is_unordered(#field_name) {
+                #unordered
+            } else {
+                #comparison
+            }
+        }
+    } else if opts.map && with_opts {
+        let subset = build_subset_map_comparison(field_name, actually, expected);
+        quote! {
+            if opts.is_subset() {
+                #subset
+            } else {
+                #comparison
+            }
+        }
+    } else {
+        comparison
+    }
+}
+
+/// Build the check that a sequence or set contains every element of expected
+fn build_subset_sequence_comparison(field_name: &str,
+                                    actually: &quote::Tokens,
+                                    expected: &quote::Tokens)
+-> quote::Tokens {
+    let render = build_rendered_value(&quote! { __element });
+    quote! {
+        {
+            let __diff = ::field_by_field::unordered_diff((#actually).iter(),
+                                                          (#expected).iter());
+            if !__diff.only_in_expected.is_empty() {
+                list.push(::field_by_field::UnequalField {
+                    field_name: #field_name.into(),
+                    actually: Box::new(::field_by_field::Rendered::absent()),
+                    expected: Box::new(__diff.missing_rendered_with(|__element| #render)),
+                    kind: ::field_by_field::DiffKind::OnlyInExpected,
+                });
+            }
+        }
+    }
+}
+
+/// Build the check that a map has every key of expected, with the same values
+///
+/// This works for anything with `.iter()` over pairs and a `.get(&key)`,
+/// reporting each entry under its key, like `headers["etag"]`.
+fn build_subset_map_comparison(field_name: &str,
+                               actually: &quote::Tokens,
+                               expected: &quote::Tokens)
+-> quote::Tokens {
+    let actual_value = build_rendered_value(&quote! { __actual_value });
+    let expected_value = build_rendered_value(&quote! { __expected_value });
+    let key = build_rendered_value(&quote! { __key });
+    quote! {
+        for (__key, __expected_value) in (#expected).iter() {
+            let __field_name = ::field_by_field::path::join(#field_name,
+                                                            &format!("[{:?}]", #key));
+            match (#actually).get(__key) {
+                None => list.push(::field_by_field::UnequalField {
+                    field_name: __field_name,
+                    actually: Box::new(::field_by_field::Rendered::absent()),
+                    expected: Box::new(#expected_value),
+                    kind: ::field_by_field::DiffKind::OnlyInExpected,
+                }),
+                Some(__actual_value) if __actual_value != __expected_value =>
+                    list.push(::field_by_field::UnequalField {
+                        field_name: __field_name,
+                        actually: Box::new(#actual_value),
+                        expected: Box::new(#expected_value),
+                        kind: ::field_by_field::DiffKind::Changed,
+                    }),
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// Build the check for a field that is compared as a multiset
+fn build_unordered_comparison(field_name: &str,
+                              actually: &quote::Tokens,
+                              expected: &quote::Tokens)
+-> quote::Tokens {
+    let render = build_rendered_value(&quote! { __element });
+    quote! {
+        {
+            let __diff = ::field_by_field::unordered_diff((#actually).iter(),
+                                                          (#expected).iter());
+            if !__diff.is_empty() {
+                list.push(::field_by_field::UnequalField {
+                    field_name: #field_name.into(),
+                    actually: Box::new(__diff.actually_rendered_with(|__element| #render)),
+                    expected: Box::new(__diff.expected_rendered_with(|__element| #render)),
+                    kind: ::field_by_field::DiffKind::Changed,
+                });
+            }
+        }
+    }
+}
+
+/// Build the `Box<Debug>` that is shown for one side of a differing field
+fn build_boxed_value(value: &quote::Tokens, opts: &FieldOpts) -> quote::Tokens {
+    if opts.redact {
+        quote! { Box::new(::field_by_field::Redacted::new(#value)) }
+    } else if opts.display {
+        quote! { Box::new(::field_by_field::Rendered::display(#value)) }
+    } else if let Some(ref debug_with) = opts.debug_with {
+        quote! {
+            Box::new(::field_by_field::Rendered::new(&::field_by_field::debug_with(
+                |__formatter: &mut ::std::fmt::Formatter| #debug_with(#value, __formatter))))
+        }
+    } else {
+        let rendered = build_rendered_value(value);
+        quote! { Box::new(#rendered) }
+    }
+}
+
+/// Build a `Rendered` from a reference, falling back to a placeholder
+///
+/// Types that don't implement `Debug` (or that aren't known to in a generic
+/// impl) are shown as `<value without Debug, type = path::to::Type>` instead
+/// of failing to compile.
+fn build_rendered_value(value: &quote::Tokens) -> quote::Tokens {
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use ::field_by_field::{ViaDebug, ViaPlaceholder};
+            (&::field_by_field::MaybeDebug(#value)).rendered()
+        }
+    }
+}
+
+/// Build `fields_not_equal` for an enum, or `fields_not_equal_with` if `with_opts` is set
 ///
 /// Same as build_fn_fields_not_equal, but a little more complex because it's
 /// iterating over variants and then checking their fields, instead of just
 /// checking fields
-fn build_fn_fields_not_equal_enum(ty_name: &syn::Ident, data: &[syn::Variant]) -> quote::Tokens {
+fn build_fn_fields_not_equal_enum(ty_name: &syn::Ident, data: &[syn::Variant], with_opts: bool)
+-> quote::Tokens {
     let is_multivariant = data.len() > 1;
+    let body = syn::Body::Enum(data.to_vec());
     let variants = data.iter()
         .map(|var| {
             let variant_name = &var.ident;
             match var.data {
                 syn::VariantData::Unit =>
-                    build_match_unit_variant(&ty_name, &variant_name, is_multivariant),
+                    build_match_unit_variant(ty_name, variant_name, &body, is_multivariant),
                 syn::VariantData::Tuple(ref fields) =>
-                    build_match_tuple_variant(&ty_name, &variant_name, fields, is_multivariant),
+                    build_match_tuple_variant(ty_name, variant_name, fields, &body,
+                                              is_multivariant, with_opts),
                 syn::VariantData::Struct(ref fields) =>
-                    build_match_struct_variant(&ty_name, &variant_name, fields, is_multivariant),
+                    build_match_struct_variant(ty_name, variant_name, fields, &body,
+                                               is_multivariant, with_opts),
             }
         });
+
+    if with_opts {
+        return quote! {
+            fn fields_not_equal_with(&self, other: &Self, opts: &::field_by_field::CompareOptions)
+            -> Vec<::field_by_field::UnequalField> {
+                #![allow(unused_mut, unused_variables)]
+                let mut list: Vec<::field_by_field::UnequalField> = Vec::new();
+
+                match (self, other) {
+                    #(#variants)*
+                }
+
+                list.retain(|field| opts.reports(field));
+                list
+            }
+        };
+    }
+
     quote! {
         fn fields_not_equal(&self, other: &Self)
         -> Vec<::field_by_field::UnequalField> {
@@ -115,18 +657,23 @@ fn build_fn_fields_not_equal_enum(ty_name: &syn::Ident, data: &[syn::Variant]) -
 ///
 /// Since this is for unit variants this doesn't check the actual value if it
 /// isn't an exact match.
-fn build_match_unit_variant(name: &syn::Ident, var_name: &syn::Ident, is_multivariant: bool)
+fn build_match_unit_variant(name: &syn::Ident,
+                            var_name: &syn::Ident,
+                            body: &syn::Body,
+                            is_multivariant: bool)
 -> quote::Tokens {
     let left_str = format!("{}::{}", &name, &var_name);
 
     if is_multivariant {
+        let expected_dump = build_debug_dump(name, body, &quote! { (*expected) });
         quote! {
             ( &#name::#var_name, &#name::#var_name ) => {}
             ( &#name::#var_name, ref expected ) => {
                 list.push(::field_by_field::UnequalField {
                     field_name: #left_str.to_string(),
                     actually: Box::new(#left_str.to_string()),
-                    expected: Box::new(format!("{:?}", &expected)),
+                    expected: Box::new(format!("{:?}", #expected_dump)),
+                    kind: ::field_by_field::DiffKind::Changed,
                 });
             }
         }
@@ -142,13 +689,15 @@ fn build_match_unit_variant(name: &syn::Ident, var_name: &syn::Ident, is_multiva
 fn build_match_tuple_variant(name: &syn::Ident,
                              var_name: &syn::Ident,
                              var_fields: &[syn::Field],
-                             is_multivariant: bool)
+                             body: &syn::Body,
+                             is_multivariant: bool,
+                             with_opts: bool)
 -> quote::Tokens {
     let actually_fields: Vec<_> = var_fields.iter()
         .enumerate()
         .map(|(i, f)| {
             assert!(f.ident.is_none(),
-                    format!("Fields in tuples should be unnamed, not {:?}", f.ident));
+                    "Fields in tuples should be unnamed, not {:?}", f.ident);
             syn::Ident::from(format!("_{}", i))
         })
         .collect();
@@ -158,7 +707,7 @@ fn build_match_tuple_variant(name: &syn::Ident,
         .enumerate()
         .map(|(i, f)| {
             assert!(f.ident.is_none(),
-                    format!("Fields in tuples should be unnamed, not {:?}", f.ident));
+                    "Fields in tuples should be unnamed, not {:?}", f.ident);
             syn::Ident::from(format!("_e{}", i))
         })
         .collect();
@@ -170,18 +719,15 @@ fn build_match_tuple_variant(name: &syn::Ident,
 
     let comparisons: Vec<_> = actually_fields.iter()
         .zip(&expected_fields)
+        .zip(var_fields)
         .enumerate()
-        .map(|(i, (actually, expected))| {
+        .map(|(i, ((actually, expected), field))| {
              let field_name = format!("{}::{}.{}", name_str, var_name_str, i);
-             quote! {
-                 if #actually != #expected {
-                     list.push(::field_by_field::UnequalField {
-                         field_name: #field_name.into(),
-                         actually: Box::new(#actually.clone()),
-                         expected: Box::new(#expected.clone()),
-                     });
-                 }
-             }
+             build_field_comparison(&field_name,
+                                    &quote! { #actually },
+                                    &quote! { #expected },
+                                    &FieldOpts::from_field(field),
+                                    with_opts)
         })
         .collect();
 
@@ -194,14 +740,17 @@ fn build_match_tuple_variant(name: &syn::Ident,
 
     if is_multivariant {
         let var_field_name = format!("{}::{}", name_str, var_name_str);
+        let actually_value = build_boxed_whole_value(name, body, &quote! { (*actually) });
+        let expected_value = build_boxed_whole_value(name, body, &quote! { (*expected) });
         // TODO: improve error messages if the variants differ
         quote! {
             #field_match
             (ref actually @ &#name::#var_name(..), ref expected) => {
                 list.push(::field_by_field::UnequalField {
                     field_name: #var_field_name.into(),
-                    actually: Box::new((*actually).clone()),
-                    expected: Box::new((*expected).clone()),
+                    actually: #actually_value,
+                    expected: #expected_value,
+                    kind: ::field_by_field::DiffKind::Changed,
                 });
             }
         }
@@ -214,7 +763,9 @@ fn build_match_tuple_variant(name: &syn::Ident,
 fn build_match_struct_variant(name: &syn::Ident,
                               var_name: &syn::Ident,
                               fields: &[syn::Field],
-                              is_multivariant: bool)
+                              body: &syn::Body,
+                              is_multivariant: bool,
+                              with_opts: bool)
 -> quote::Tokens {
     let field_names = fields.iter().cloned()
         .map(|field| {
@@ -226,28 +777,32 @@ fn build_match_struct_variant(name: &syn::Ident,
     let expected_names = field_names.iter()
         .map(|name| format!("expected_{}", name).into())
         .collect::<Vec<syn::Ident>>();
-    let expected_name_bindings = field_names.iter().zip(&expected_names)
-        .map(|(name, expected_name)| {
-            quote! { #name: ref #expected_name }
+    let skipped = fields.iter()
+        .map(|field| FieldOpts::from_field(field).skip)
+        .collect::<Vec<_>>();
+    let actually_name_bindings = field_names.iter().zip(&skipped)
+        .map(|(name, &skip)| {
+            if skip { quote! { #name: _ } } else { quote! { ref #name } }
+        })
+        .collect::<Vec<_>>();
+    let expected_name_bindings = field_names.iter().zip(&expected_names).zip(&skipped)
+        .map(|((name, expected_name), &skip)| {
+            if skip { quote! { #name: _ } } else { quote! { #name: ref #expected_name } }
         })
         .collect::<Vec<_>>();
     let comparisons = field_names.iter()
         .zip(&expected_names)
-        .map(|(name, other_name)| {
-            let vname = name.as_ref();
-            quote! {
-                if #name != #other_name {
-                    list.push(::field_by_field::UnequalField {
-                        field_name: #vname.into(),
-                        actually: Box::new(#name.clone()),
-                        expected: Box::new(#other_name.clone()),
-                    })
-                }
-            }
+        .zip(fields)
+        .map(|((name, other_name), field)| {
+            build_field_comparison(name.as_ref(),
+                                   &quote! { #name },
+                                   &quote! { #other_name },
+                                   &FieldOpts::from_field(field),
+                                   with_opts)
         })
         .collect::<Vec<_>>();
     let by_field_match = quote! {
-        (&#name::#var_name { #(ref #field_names),* },
+        (&#name::#var_name { #(#actually_name_bindings),* },
          &#name::#var_name { #(#expected_name_bindings,)* }) => {
             #(#comparisons)*
         }
@@ -255,13 +810,16 @@ fn build_match_struct_variant(name: &syn::Ident,
 
     if is_multivariant {
         let vname = format!("{}::{}", name, var_name);
+        let actually_value = build_boxed_whole_value(name, body, &quote! { (*actually) });
+        let expected_value = build_boxed_whole_value(name, body, &quote! { (*expected) });
         quote! {
             #by_field_match
-            (ref actually @ &#name::#var_name { .. }, expected) => {
+            (ref actually @ &#name::#var_name { .. }, ref expected) => {
                 list.push(::field_by_field::UnequalField {
                     field_name: #vname.into(),
-                    actually: Box::new((*actually).clone()),
-                    expected: Box::new((*expected).clone()),
+                    actually: #actually_value,
+                    expected: #expected_value,
+                    kind: ::field_by_field::DiffKind::Changed,
                 })
             }
         }
@@ -272,35 +830,179 @@ fn build_match_struct_variant(name: &syn::Ident,
 
 /// Build a function that panics if the result of fiels_not_equal is non-empty
 ///
-/// The Function will panic with a nice error message
-fn build_fn_assert_equal_field_by_field(name: &syn::Ident) -> quote::Tokens {
-    let overall_errmsgfmt = quote! {
-        let ac_exp = format!("    actually: {:?}\n    \
-                              expected: {:?}\n", self, other);
-        errmsg.push_str(&ac_exp);
-    };
+/// The Function will panic with a nice error message, showing the differing
+/// fields as an indented tree.
+fn build_fn_assert_equal_field_by_field(name: &syn::Ident, body: &syn::Body) -> quote::Tokens {
+    let actually_dump = build_debug_dump(name, body, &quote! { self });
+    let expected_dump = build_debug_dump(name, body, &quote! { other });
 
-    let field_errmsgfmt = quote! {
-        errmsg.push_str(&format!("        {}: {:?} != {:?}\n",
-                                 field_err.field_name,
-                                 field_err.actually, field_err.expected));
+    quote! {
+        fn assert_equal_field_by_field(&self, other: &Self) {
+            let diff = ::field_by_field::EqualFieldByField::diff(self, other);
 
-    };
+            if !diff.is_empty() {
+                panic!("{}", ::field_by_field::not_equal_message(&diff,
+                                                                 &#actually_dump,
+                                                                 &#expected_dump));
+            }
+        }
+    }
+}
 
+/// Build `debug_dump`, which shows `self` the same way assert_equal_field_by_field does
+fn build_fn_debug_dump(name: &syn::Ident, body: &syn::Body) -> quote::Tokens {
+    let dump = build_debug_dump(name, body, &quote! { self });
     quote! {
-        fn assert_equal_field_by_field(&self, other: &#name) {
-            let errs = self.fields_not_equal(other);
+        fn debug_dump(&self) -> ::field_by_field::Rendered {
+            ::field_by_field::Rendered::new(&#dump)
+        }
+    }
+}
 
-            if errs.len() > 0 {
-                let mut errmsg = String::from("\n    Items are not equal:\n");
-                for field_err in errs {
-                    #field_errmsgfmt
+/// Build an expression that can be `Debug` formatted to show a whole value
+///
+/// This is just `value`'s `Debug` output unless some field is redacted, in
+/// which case it mimics `#[derive(Debug)]` but hides the redacted fields.
+fn build_debug_dump(name: &syn::Ident, body: &syn::Body, value: &quote::Tokens) -> quote::Tokens {
+    if !has_redacted_fields(body) {
+        return build_rendered_value(value);
+    }
+
+    let fmt_body = match *body {
+        syn::Body::Struct(ref data) => {
+            let bindings = build_field_bindings(data.fields());
+            let debug_fields = build_debug_fields(name.as_ref(), data);
+            match *data {
+                syn::VariantData::Struct(_) => quote! {
+                    let #name { #(#bindings),* } = *#value;
+                    #debug_fields
+                },
+                syn::VariantData::Tuple(_) => quote! {
+                    let #name ( #(#bindings),* ) = *#value;
+                    #debug_fields
+                },
+                syn::VariantData::Unit => debug_fields,
+            }
+        }
+        syn::Body::Enum(ref variants) => {
+            let arms = variants.iter().map(|var| {
+                let var_name = &var.ident;
+                let bindings = build_field_bindings(var.data.fields());
+                let debug_fields = build_debug_fields(var_name.as_ref(), &var.data);
+                match var.data {
+                    syn::VariantData::Struct(_) => quote! {
+                        #name::#var_name { #(#bindings),* } => { #debug_fields }
+                    },
+                    syn::VariantData::Tuple(_) => quote! {
+                        #name::#var_name ( #(#bindings),* ) => { #debug_fields }
+                    },
+                    syn::VariantData::Unit => quote! {
+                        #name::#var_name => { #debug_fields }
+                    },
+                }
+            });
+            quote! {
+                match *#value {
+                    #(#arms)*
                 }
+            }
+        }
+    };
 
-                #overall_errmsgfmt
+    quote! {
+        ::field_by_field::debug_with(|__formatter: &mut ::std::fmt::Formatter| { #fmt_body })
+    }
+}
+
+/// Build the `Box<Debug>` for a whole value, e.g. when enum variants differ
+fn build_boxed_whole_value(name: &syn::Ident, body: &syn::Body, value: &quote::Tokens)
+-> quote::Tokens {
+    if has_redacted_fields(body) {
+        let dump = build_debug_dump(name, body, value);
+        quote! { Box::new(::field_by_field::Rendered::new(&#dump)) }
+    } else {
+        let rendered = build_rendered_value(value);
+        quote! { Box::new(#rendered) }
+    }
+}
+
+/// True if dumping the whole value with `Debug` could show a redacted field
+///
+/// That's the case for our own `redact` fields, and for fields whose type is
+/// compared with its own `EqualFieldByField` impl, which may redact fields too.
+fn has_redacted_fields(body: &syn::Body) -> bool {
+    let may_redact = |f: &syn::Field| {
+        let opts = FieldOpts::from_field(f);
+        opts.redact || opts.nested || opts.flatten || opts.align
+    };
+    match *body {
+        syn::Body::Struct(ref data) => data.fields().iter().any(may_redact),
+        syn::Body::Enum(ref variants) => variants.iter()
+            .flat_map(|v| v.data.fields())
+            .any(may_redact),
+    }
+}
+
+/// True for fields that are compared element by element with `EqualFieldByField`
+fn is_element_wise(opts: &FieldOpts) -> bool {
+    opts.align || (opts.nested && (opts.unordered || opts.sequence))
+}
 
-                panic!("{}", errmsg);
+/// Build `ref` patterns binding every field, in declaration order
+///
+/// Struct fields are bound to their own names, tuple fields to `_0`, `_1`...
+fn build_field_bindings(fields: &[syn::Field]) -> Vec<quote::Tokens> {
+    fields.iter()
+        .enumerate()
+        .map(|(i, f)| match f.ident {
+            Some(ref ident) => quote! { ref #ident },
+            None => {
+                let ident = syn::Ident::from(format!("_{}", i));
+                quote! { ref #ident }
             }
-        }
+        })
+        .collect()
+}
+
+/// Build the body of a `Debug::fmt` call for fields bound by build_field_bindings
+fn build_debug_fields(name: &str, data: &syn::VariantData) -> quote::Tokens {
+    let fields = data.fields().iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let (field_str, binding) = match f.ident {
+                Some(ref ident) => (ident.to_string(), ident.clone()),
+                None => (i.to_string(), syn::Ident::from(format!("_{}", i))),
+            };
+            let opts = FieldOpts::from_field(f);
+            let value = if opts.redact {
+                quote! { &::field_by_field::Redacted::new(#binding) }
+            } else if is_element_wise(&opts) {
+                quote! {
+                    &::field_by_field::debug_with(|__formatter: &mut ::std::fmt::Formatter| {
+                        __formatter.debug_list()
+                            .entries((#binding).iter()
+                                     .map(::field_by_field::EqualFieldByField::debug_dump))
+                            .finish()
+                    })
+                }
+            } else if opts.nested || opts.flatten {
+                quote! { &::field_by_field::EqualFieldByField::debug_dump(#binding) }
+            } else {
+                let rendered = build_rendered_value(&quote! { #binding });
+                quote! { &#rendered }
+            };
+            match *data {
+                syn::VariantData::Struct(_) => quote! { .field(#field_str, #value) },
+                _ => quote! { .field(#value) },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match *data {
+        syn::VariantData::Struct(_) =>
+            quote! { __formatter.debug_struct(#name) #(#fields)* .finish() },
+        syn::VariantData::Tuple(_) =>
+            quote! { __formatter.debug_tuple(#name) #(#fields)* .finish() },
+        syn::VariantData::Unit => quote! { __formatter.write_str(#name) },
     }
 }
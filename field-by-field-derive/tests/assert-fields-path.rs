@@ -0,0 +1,26 @@
+//! Test crate for calling `assert_fields!` by path, without `#[macro_use]`
+
+extern crate field_by_field;
+
+struct Body {
+    kind: &'static str,
+    len: usize,
+}
+
+struct Response {
+    status: u16,
+    body: Body,
+}
+
+#[test]
+fn nested_patterns_work_by_path() {
+    let response = Response { status: 200, body: Body { kind: "json", len: 12 } };
+    field_by_field::assert_fields!(response, Response { status: 200, body: Body { len: 12, .. } });
+}
+
+#[test]
+#[should_panic(expected = "body.kind")]
+fn differences_are_reported_by_path() {
+    let response = Response { status: 200, body: Body { kind: "json", len: 12 } };
+    field_by_field::assert_fields!(response, Response { body: Body { kind: "html", .. }, .. });
+}
@@ -0,0 +1,76 @@
+//! Test crate for `assert_fields!`
+
+#[macro_use]
+extern crate field_by_field;
+
+mod api {
+    #[derive(Debug)]
+    pub struct Body {
+        pub kind: String,
+        pub len: usize,
+    }
+
+    #[derive(Debug)]
+    pub struct Response {
+        pub status: u16,
+        pub body: Body,
+        pub retry_after: Option<u32>,
+    }
+}
+
+/// Doesn't implement Debug, PartialEq or FieldByField
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+use api::{Body, Response};
+
+fn response() -> Response {
+    Response {
+        status: 200,
+        body: Body { kind: "json".into(), len: 12 },
+        retry_after: None,
+    }
+}
+
+#[test]
+fn listed_fields_match() {
+    assert_fields!(response(), Response { status: 200, .. });
+    assert_fields!(response(), Response { body: Body { kind: "json", .. }, .. });
+    assert_fields!(response(), api::Response {
+        status: 200,
+        body: api::Body { kind: "json", len: 12 },
+        retry_after: None,
+    });
+}
+
+#[test]
+fn types_without_any_traits() {
+    let point = Point { x: 1, y: 2 };
+    assert_fields!(point, Point { x: 1, y: 2 });
+}
+
+#[test]
+#[should_panic(expected = "
+    Item does not match expectation:
+        status: 200 != 404
+        body.kind: \"json\" != \"html\"
+    actually: Response { status: 200, body: Body { kind: \"json\", len: 12 }, retry_after: None }
+")]
+fn mismatches_are_reported_with_paths() {
+    assert_fields!(response(), Response { status: 404, body: Body { kind: "html", .. }, .. });
+}
+
+#[test]
+#[should_panic(expected = "retry_after: None != Some(5)")]
+fn expressions_are_compared() {
+    let seconds = 5;
+    assert_fields!(response(), Response { retry_after: Some(seconds), .. });
+}
+
+#[test]
+#[should_panic(expected = "actually: <value without Debug, type = assert_fields::Point>")]
+fn types_without_debug_still_fail() {
+    assert_fields!(Point { x: 1, y: 2 }, Point { y: 3, .. });
+}
@@ -0,0 +1,57 @@
+//! Test crate for table-driven comparisons
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{assert_cases, compare_cases};
+
+#[derive(FieldByField, Debug)]
+struct Cart {
+    items: usize,
+    total: u32,
+}
+
+fn checkout(prices: &[u32]) -> Cart {
+    Cart { items: prices.len(), total: prices.iter().sum() }
+}
+
+#[test]
+fn passing_cases() {
+    assert_cases(vec![
+        ("empty_cart", checkout(&[]), Cart { items: 0, total: 0 }),
+        ("one_item", checkout(&[3]), Cart { items: 1, total: 3 }),
+    ]);
+}
+
+#[test]
+fn every_failing_case_is_reported() {
+    let report = compare_cases(vec![
+        ("empty_cart", checkout(&[]), Cart { items: 0, total: 1 }),
+        ("one_item", checkout(&[3]), Cart { items: 1, total: 3 }),
+        ("two_items", checkout(&[1, 1]), Cart { items: 1, total: 3 }),
+    ]);
+
+    assert!(!report.is_success());
+    assert_eq!(report.passed, vec!["one_item"]);
+    assert_eq!(report.to_string(), "    case `empty_cart`: total: 0 != 1
+    case `two_items`:
+        items: 2 != 1
+        total: 2 != 3
+
+    2 of 3 cases failed, 1 passed
+");
+}
+
+#[test]
+#[should_panic(expected = "case `empty_cart`: total: 0 != 1")]
+fn assert_cases_panics_with_the_report() {
+    let names = vec!["empty_cart".to_string(), "one_item".to_string()];
+    let prices: Vec<&[u32]> = vec![&[], &[3]];
+    let expected = vec![Cart { items: 0, total: 1 }, Cart { items: 1, total: 3 }];
+
+    assert_cases(names.into_iter()
+                     .zip(prices)
+                     .zip(expected)
+                     .map(|((name, prices), expected)| (name, checkout(prices), expected)));
+}
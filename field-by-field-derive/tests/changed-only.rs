@@ -0,0 +1,77 @@
+//! Test crate for `assert_changed_only`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+#[derive(FieldByField, Debug, Clone)]
+struct Customer {
+    name: &'static str,
+    email: &'static str,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Ticket {
+    status: &'static str,
+    priority: u8,
+    updated_at: u64,
+    #[field_by_field(nested)]
+    customer: Customer,
+}
+
+fn ticket() -> Ticket {
+    Ticket {
+        status: "open",
+        priority: 1,
+        updated_at: 100,
+        customer: Customer { name: "bob", email: "bob@example.com" },
+    }
+}
+
+#[test]
+fn exactly_the_listed_fields_changed() {
+    let before = ticket();
+    let after = Ticket { status: "closed", updated_at: 200, ..before.clone() };
+
+    before.assert_changed_only(&after, &["status", "updated_at"]);
+}
+
+#[test]
+fn nested_paths_and_globs() {
+    let before = ticket();
+    let mut after = before.clone();
+    after.customer.email = "robert@example.com";
+    after.updated_at = 200;
+
+    before.assert_changed_only(&after, &["customer.email", "*_at"]);
+    before.assert_changed_only(&after, &["customer", "updated_at"]);
+}
+
+#[test]
+#[should_panic(expected = "
+    Fields changed that weren't expected to:
+        priority: 1 != 3
+")]
+fn other_changes_fail() {
+    let before = ticket();
+    let after = Ticket { status: "closed", priority: 3, ..before.clone() };
+
+    before.assert_changed_only(&after, &["status"]);
+}
+
+#[test]
+#[should_panic(expected = "
+    Fields changed that weren't expected to:
+        customer.name: \"bob\" != \"robert\"
+    Fields expected to change that didn't:
+        updated_at
+")]
+fn listed_fields_that_stayed_the_same_fail() {
+    let before = ticket();
+    let mut after = Ticket { status: "closed", ..before.clone() };
+    after.customer.name = "robert";
+
+    before.assert_changed_only(&after, &["status", "updated_at"]);
+}
@@ -0,0 +1,122 @@
+//! Test crate for the `compare()` builder
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::panic;
+
+use field_by_field::{CompareOptions, EqualFieldByField};
+
+#[derive(FieldByField, Debug)]
+struct Sensor {
+    name: String,
+    aliases: Vec<String>,
+}
+
+#[derive(FieldByField, Debug)]
+struct Reading {
+    id: u32,
+    value: f64,
+    tags: Vec<&'static str>,
+    #[field_by_field(nested)]
+    sensor: Sensor,
+}
+
+#[derive(FieldByField, Debug)]
+struct Login {
+    user: String,
+    #[field_by_field(redact)]
+    password: String,
+}
+
+fn reading(id: u32, value: f64, tags: Vec<&'static str>, aliases: &[&str]) -> Reading {
+    Reading {
+        id,
+        value,
+        tags,
+        sensor: Sensor {
+            name: "probe".into(),
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+        },
+    }
+}
+
+fn paths(reading: &Reading, other: &Reading) -> Vec<String> {
+    reading.compare(other).diff().leaves().iter().map(|leaf| leaf.path.clone()).collect()
+}
+
+#[test]
+fn without_options_compare_is_fields_not_equal() {
+    let got = reading(1, 0.5, vec!["a", "b"], &["x"]);
+    let want = reading(2, 0.5, vec!["b", "a"], &["x"]);
+
+    assert_eq!(paths(&got, &want), vec!["id", "tags"]);
+    assert!(got.compare(&want).result().is_err());
+}
+
+#[test]
+fn every_option_together() {
+    let got = reading(1, 0.1 + 0.2, vec!["a", "b"], &["x", "y"]);
+    let want = reading(2, 0.3, vec!["b", "a"], &["y", "x"]);
+
+    got.compare(&want)
+        .ignoring(&["id"])
+        .with_float_tolerance(1e-9)
+        .unordered("tags")
+        .unordered("sensor.aliases")
+        .labels("got", "want")
+        .assert();
+}
+
+#[test]
+fn float_tolerance_does_not_apply_to_integers() {
+    let got = reading(41, 0.5, vec![], &[]);
+    let want = reading(42, 1.0, vec![], &[]);
+
+    let diff = got.compare(&want).with_float_tolerance(1.0).diff();
+    assert_eq!(diff.to_string(), "id: 41 != 42\n");
+}
+
+#[test]
+fn unordered_still_reports_different_elements() {
+    let got = reading(1, 0.5, vec!["a", "b"], &[]);
+    let want = reading(1, 0.5, vec!["b", "c"], &[]);
+
+    let diff = got.compare(&want).unordered("tags").result().unwrap_err();
+    assert_eq!(diff.to_string(), "tags: only in actual: [\"a\"] != only in expected: [\"c\"]\n");
+}
+
+#[test]
+fn options_can_be_loaded_then_extended() {
+    let got = reading(1, 0.5, vec![], &[]);
+    let want = reading(2, 0.6, vec![], &[]);
+    let opts = CompareOptions::from_config("value = \"rel 20%\"").unwrap();
+
+    assert_eq!(got.compare(&want).with_options(opts).ignoring(vec!["id"]).result().ok(),
+               Some(()));
+}
+
+#[test]
+#[should_panic(expected = "\
+    Items are not equal:
+        id: 1 != 2
+     got: Reading")]
+fn labels_are_used_in_the_message() {
+    let got = reading(1, 0.5, vec![], &[]);
+    let want = reading(2, 0.5, vec![], &[]);
+
+    got.compare(&want).labels("got", "want").assert();
+}
+
+#[test]
+fn redacted_fields_are_hidden_in_the_whole_values() {
+    let got = Login { user: "bob".into(), password: "hunter2".into() };
+    let want = Login { user: "alice".into(), password: "hunter2".into() };
+
+    let err = panic::catch_unwind(|| got.compare(&want).assert()).expect_err("should panic");
+    let message = err.downcast::<String>().expect("panic message should be a String");
+    assert!(message.contains("actually: Login { user: \"bob\", password: <redacted, hash "),
+            "{}", message);
+    assert!(!message.contains("hunter2"), "{}", message);
+}
@@ -0,0 +1,200 @@
+//! Test crate for tolerances and rules loaded from comparison configs
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::env;
+use std::fs;
+
+use field_by_field::{Approximate, CompareOptions, EqualFieldByField, Tolerance};
+
+#[derive(FieldByField, Debug)]
+struct Stage {
+    latency_ms: u32,
+    rows: u64,
+}
+
+#[derive(FieldByField, Debug)]
+struct Report {
+    revenue: f64,
+    #[field_by_field(nested)]
+    load: Stage,
+    #[field_by_field(nested)]
+    transform: Stage,
+    debug_info: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Price(f64);
+
+impl Approximate for Price {
+    fn is_close_to(&self, expected: &Price, tolerance: Tolerance) -> bool {
+        self.0.is_close_to(&expected.0, tolerance)
+    }
+}
+
+#[derive(FieldByField, Debug)]
+struct Quote {
+    bid: Option<f64>,
+    ask: Price,
+    #[field_by_field(display)]
+    size: u32,
+}
+
+#[derive(FieldByField, Debug)]
+enum Fill {
+    Partial { price: f64 },
+}
+
+fn report(revenue: f64, latency_ms: u32, rows: u64, debug_info: &str) -> Report {
+    Report {
+        revenue,
+        load: Stage { latency_ms, rows },
+        transform: Stage { latency_ms: latency_ms * 2, rows },
+        debug_info: debug_info.into(),
+    }
+}
+
+fn names(list: Vec<field_by_field::UnequalField>) -> Vec<String> {
+    list.into_iter().map(|ue| ue.field_name).collect()
+}
+
+const TOML: &str = r#"
+# tuned by hand, see the pipeline dashboard
+revenue = "abs 0.01"
+"*.latency_ms" = "rel 5%"   # timings are noisy
+debug_info = "ignore"
+"#;
+
+#[test]
+fn tolerances_allow_small_differences() {
+    let opts = CompareOptions::from_config(TOML).unwrap();
+    let actual = report(100.004, 1000, 10, "run 1");
+    let expected = report(100.0, 1040, 10, "run 2");
+
+    assert!(actual.fields_not_equal_with(&expected, &opts).is_empty());
+}
+
+#[test]
+fn differences_beyond_tolerances_are_reported() {
+    let opts = CompareOptions::from_config(TOML).unwrap();
+    let actual = report(100.02, 1000, 11, "run 1");
+    let expected = report(100.0, 1100, 10, "run 2");
+
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &opts)),
+               vec!["revenue", "load.latency_ms", "load.rows",
+                    "transform.latency_ms", "transform.rows"]);
+}
+
+#[test]
+fn json_configs_are_the_same_as_toml() {
+    let json = CompareOptions::from_config(r#"{
+        "revenue": "abs 0.01",
+        "*.latency_ms": "rel 0.05",
+        "debug_info": "ignore"
+    }"#).unwrap();
+    let actual = report(100.004, 1000, 11, "run 1");
+    let expected = report(100.0, 1040, 10, "run 2");
+
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &json)),
+               vec!["load.rows", "transform.rows"]);
+}
+
+#[test]
+fn strings_can_be_escaped_or_literal() {
+    let json = r#"{"tags[\"a\tb\"]": "ignore", "\u00e9t\u00e9": "ignore"}"#;
+    let opts = CompareOptions::from_config(json).unwrap();
+    assert!(opts.is_ignored("tags[\"a\tb\"]"));
+    assert!(opts.is_ignored("\u{e9}t\u{e9}"));
+
+    let opts = CompareOptions::from_config(r#"
+        'notes["\n"]' = 'ignore'
+        "\U0001F600" = "ignore"
+    "#).unwrap();
+    assert!(opts.is_ignored("notes[\"\\n\"]"));
+    assert!(opts.is_ignored("\u{1F600}"));
+}
+
+#[test]
+fn tables_and_objects_add_to_the_path() {
+    let toml = CompareOptions::from_config(r#"
+        transform.rows = "ignore"
+
+        [load]
+        latency_ms = "rel 5%"
+    "#).unwrap();
+    let json = CompareOptions::from_config(r#"{
+        "transform": {"rows": "ignore"},
+        "load": {"latency_ms": "rel 5%"}
+    }"#).unwrap();
+
+    for opts in &[toml, json] {
+        assert!(opts.is_ignored("transform.rows"));
+        assert_eq!(opts.tolerance_for("load.latency_ms"), Some(Tolerance::Relative(0.05)));
+        assert_eq!(opts.tolerance_for("transform.latency_ms"), None);
+    }
+}
+
+#[test]
+fn values_must_be_rules() {
+    let err = CompareOptions::from_config("debug_info = \"ignore\"\nrevenue = 0.01").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: expected a rule string or a table for `revenue`");
+
+    let err = CompareOptions::from_config(r#"{"load": {"rows": [1, 2]}}"#).unwrap_err();
+    assert_eq!(err.to_string(), "expected a rule string or a table for `load.rows`");
+}
+
+#[test]
+fn tolerances_apply_to_typed_fields() {
+    let opts = CompareOptions::new().tolerance("**", Tolerance::Absolute(0.01));
+    let actual = Quote { bid: Some(1.0), ask: Price(2.0), size: 100 };
+
+    let close = Quote { bid: Some(1.0000001), ask: Price(2.001), size: 100 };
+    assert!(actual.fields_not_equal_with(&close, &opts).is_empty());
+
+    let far = Quote { bid: None, ask: Price(2.5), size: 101 };
+    assert_eq!(names(actual.fields_not_equal_with(&far, &opts)), vec!["bid", "ask", "size"]);
+
+    let fill = Fill::Partial { price: 1.0 };
+    assert!(fill.fields_not_equal_with(&Fill::Partial { price: 1.001 }, &opts).is_empty());
+    assert_eq!(names(fill.fields_not_equal_with(&Fill::Partial { price: 1.1 }, &opts)),
+               vec!["price"]);
+}
+
+#[test]
+fn later_tolerances_win() {
+    let opts = CompareOptions::new()
+        .tolerance("**", Tolerance::Absolute(1000.0))
+        .tolerance("revenue", Tolerance::Absolute(0.0));
+
+    assert_eq!(opts.tolerance_for("load.rows"), Some(Tolerance::Absolute(1000.0)));
+    assert_eq!(opts.tolerance_for("revenue"), Some(Tolerance::Absolute(0.0)));
+}
+
+#[test]
+fn config_files_are_loaded() {
+    let file = env::temp_dir().join(format!("field-by-field-{}.toml", std::process::id()));
+    fs::write(&file, TOML).unwrap();
+    let opts = CompareOptions::from_config_file(&file);
+    fs::remove_file(&file).unwrap();
+
+    assert!(opts.unwrap().is_ignored("debug_info"));
+}
+
+#[test]
+fn bad_configs_say_where_the_problem_is() {
+    let err = CompareOptions::from_config("revenue = \"abs 0.01\"\nrows = \"close enough\"\n")
+        .unwrap_err();
+    assert_eq!(err.line, Some(2));
+    assert!(err.to_string().contains("unknown rule \"close enough\" for `rows`"), "{}", err);
+
+    let err = CompareOptions::from_config("{\n  \"revenue\": \"abs x\"\n}").unwrap_err();
+    assert_eq!(err.to_string(), "expected a number for `revenue`, not `x`");
+
+    let err = CompareOptions::from_config("revenue \"abs 0.01\"").unwrap_err();
+    assert_eq!(err.line, Some(1));
+
+    let err = CompareOptions::from_config("{\n  \"revenue\": \"abs 0.01\",\n}").unwrap_err();
+    assert_eq!(err.line, Some(3));
+}
@@ -0,0 +1,136 @@
+//! Test crate for runtime `CompareOptions`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{CompareOptions, EqualFieldByField};
+use field_by_field::path::{join, matches, segments};
+
+#[derive(FieldByField, Debug, Clone)]
+struct Customer {
+    id: u32,
+    name: String,
+    updated_at: u64,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Order {
+    id: u32,
+    #[field_by_field(nested)]
+    customer: Customer,
+    updated_at: u64,
+}
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Item {
+    id: u32,
+    qty: u32,
+}
+
+#[derive(FieldByField, Debug)]
+struct Cart {
+    #[field_by_field(nested)]
+    items: Vec<Item>,
+    saved: Vec<Item>,
+}
+
+fn cart(ids: &[u32], qty: u32) -> Cart {
+    let items: Vec<Item> = ids.iter().map(|&id| Item { id, qty }).collect();
+    Cart { items: items.clone(), saved: items }
+}
+
+fn order(id: u32, name: &str, updated_at: u64) -> Order {
+    Order {
+        id,
+        customer: Customer { id, name: name.into(), updated_at },
+        updated_at,
+    }
+}
+
+fn names(list: Vec<field_by_field::UnequalField>) -> Vec<String> {
+    list.into_iter().map(|ue| ue.field_name).collect()
+}
+
+#[test]
+fn glob_patterns() {
+    assert!(matches("**.updated_at", "updated_at"));
+    assert!(matches("**.updated_at", "customer.updated_at"));
+    assert!(matches("items[*].id", "items[12].id"));
+    assert!(matches("*.id", "customer.id"));
+    assert!(matches("*_at", "created_at"));
+    assert!(matches("customer", "customer.address.zip"));
+    assert!(matches("**", "anything.at[all]"));
+
+    assert!(!matches("*.id", "id"));
+    assert!(!matches("items[*].id", "items[12].qty"));
+    assert!(!matches("customer.id", "customer"));
+    assert!(!matches("updated_at", "customer.updated_at"));
+}
+
+#[test]
+fn expected_side_indexes() {
+    assert_eq!(segments("steps[1]~[2].secs"), vec!["steps", "[1]~[2]", "secs"]);
+    assert_eq!(segments("steps~[0]"), vec!["steps", "~[0]"]);
+    assert_eq!(join("steps", "~[0]"), "steps~[0]");
+
+    assert!(matches("steps[*].secs", "steps[1]~[2].secs"));
+    assert!(matches("steps~[*]", "steps~[0]"));
+    assert!(!matches("steps[*]", "steps~[0]"));
+}
+
+#[test]
+fn quoted_keys_are_single_segments() {
+    assert_eq!(segments(r#"h["a]b"].c"#), vec!["h", r#"["a]b"]"#, "c"]);
+    assert_eq!(segments(r#"h["x\"]"].c"#), vec!["h", r#"["x\"]"]"#, "c"]);
+    assert_eq!(segments("h[']'].c"), vec!["h", "[']']", "c"]);
+
+    assert!(matches(r#"h["a]b"]"#, r#"h["a]b"].c"#));
+    assert!(matches("h[*].c", r#"h["a]b"].c"#));
+}
+
+#[test]
+fn no_options_is_the_same_as_fields_not_equal() {
+    let (actual, expected) = (order(1, "bob", 1), order(2, "alice", 2));
+
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &CompareOptions::new())),
+               names(actual.fields_not_equal(&expected)));
+}
+
+#[test]
+fn ignored_paths_are_not_reported() {
+    let (actual, expected) = (order(1, "bob", 1), order(2, "alice", 2));
+    let opts = CompareOptions::new().ignore("**.updated_at").ignore("*.id");
+
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &opts)),
+               vec!["id", "customer.name"]);
+}
+
+#[test]
+fn ignoring_a_parent_ignores_its_children() {
+    let (actual, expected) = (order(1, "bob", 1), order(1, "alice", 1));
+    let opts = CompareOptions::new().ignore("customer");
+
+    assert!(actual.fields_not_equal_with(&expected, &opts).is_empty());
+}
+
+#[test]
+fn ignore_reaches_into_nested_sequence_elements() {
+    let (actual, expected) = (cart(&[1, 2], 1), cart(&[3, 4], 1));
+    let opts = CompareOptions::new().ignore("items[*].id");
+
+    assert_eq!(names(actual.fields_not_equal(&expected)),
+               vec!["items[0].id", "items[1].id", "saved"]);
+    // `saved` isn't `nested`, so it's compared whole and has no `saved[n].id` paths
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &opts)), vec!["saved"]);
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &opts.ignore("saved"))),
+               Vec::<String>::new());
+}
+
+#[test]
+fn nested_sequences_of_different_lengths() {
+    let (actual, expected) = (cart(&[1, 2, 3], 1), cart(&[1, 2], 2));
+
+    assert_eq!(names(actual.fields_not_equal_with(&expected, &CompareOptions::new())),
+               vec!["items[0].qty", "items[1].qty", "items[2]", "saved"]);
+}
@@ -0,0 +1,142 @@
+//! Test crate for `check_consistency` between PartialEq and fields_not_equal
+
+// Drifted's manual PartialEq that disagrees with its derived Hash is the point
+#![allow(clippy::derived_hash_with_manual_eq)]
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::panic;
+
+use field_by_field::{check_consistency, check_consistency_with_hash};
+
+#[derive(FieldByField, Debug, Clone, PartialEq, Hash)]
+struct Consistent {
+    a: u8,
+    b: String,
+}
+
+/// Someone added `b` but forgot to update `PartialEq`
+#[derive(FieldByField, Debug, Clone, Hash)]
+struct Drifted {
+    a: u8,
+    b: String,
+}
+
+impl PartialEq for Drifted {
+    fn eq(&self, other: &Drifted) -> bool {
+        self.a == other.a
+    }
+}
+
+/// Compares everything but the password
+#[derive(FieldByField, Debug)]
+struct Account {
+    user: &'static str,
+    #[field_by_field(redact)]
+    password: &'static str,
+}
+
+impl PartialEq for Account {
+    fn eq(&self, other: &Account) -> bool {
+        self.user == other.user
+    }
+}
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Line {
+    sku: &'static str,
+    qty: u32,
+}
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Order {
+    #[field_by_field(unordered, nested)]
+    lines: Vec<Line>,
+}
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Transcript {
+    #[field_by_field(align)]
+    lines: Vec<Line>,
+}
+
+fn line(sku: &'static str, qty: u32) -> Line {
+    Line { sku, qty }
+}
+
+#[test]
+fn consistent_types_pass() {
+    let values = vec![
+        Consistent { a: 1, b: "x".into() },
+        Consistent { a: 1, b: "y".into() },
+        Consistent { a: 2, b: "x".into() },
+    ];
+    for a in &values {
+        for b in &values {
+            check_consistency_with_hash(a, b);
+        }
+    }
+}
+
+#[test]
+fn drifted_types_pass_while_the_new_field_is_equal() {
+    check_consistency(&Drifted { a: 1, b: "x".into() }, &Drifted { a: 2, b: "x".into() });
+}
+
+#[test]
+#[should_panic(expected = "a == b, but these fields differ: b")]
+fn drifted_types_fail() {
+    check_consistency(&Drifted { a: 1, b: "x".into() }, &Drifted { a: 1, b: "y".into() });
+}
+
+#[test]
+#[should_panic(expected = "a == b, but they hash differently")]
+fn drifted_hashes_fail() {
+    check_consistency_with_hash(&Drifted { a: 1, b: "x".into() },
+                                &Drifted { a: 1, b: "y".into() });
+}
+
+#[test]
+fn redacted_fields_are_hidden() {
+    let a = Account { user: "bob", password: "hunter2" };
+    let b = Account { user: "bob", password: "hunter3" };
+
+    let err = panic::catch_unwind(|| check_consistency(&a, &b)).expect_err("should panic");
+    let message = err.downcast::<String>().expect("panic message should be a String");
+    assert!(message.contains("a: Account { user: \"bob\", password: <redacted, hash "),
+            "{}", message);
+    assert!(!message.contains("hunter"), "{}", message);
+}
+
+#[test]
+fn paired_sequences_pass_both_ways() {
+    let values = vec![
+        vec![line("a", 1), line("b", 2)],
+        vec![line("b", 3), line("a", 1)],
+        vec![line("a", 1)],
+        vec![],
+    ];
+    for a in &values {
+        for b in &values {
+            check_consistency(&Order { lines: a.clone() }, &Order { lines: b.clone() });
+        }
+    }
+}
+
+#[test]
+fn aligned_sequences_pass_both_ways() {
+    let values = vec![
+        vec![line("x", 1)],
+        vec![line("x", 1), line("y", 1)],
+        vec![line("y", 2), line("x", 1)],
+        vec![],
+    ];
+    for a in &values {
+        for b in &values {
+            check_consistency(&Transcript { lines: a.clone() },
+                              &Transcript { lines: b.clone() });
+        }
+    }
+}
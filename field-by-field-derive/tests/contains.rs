@@ -0,0 +1,74 @@
+//! Test crate for `assert_contains_field_by_field`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::panic;
+
+use field_by_field::{assert_contains_field_by_field, closest_match};
+
+#[derive(FieldByField, Debug)]
+struct Event {
+    id: u32,
+    kind: &'static str,
+    timestamp: u64,
+}
+
+#[derive(FieldByField, Debug)]
+struct Session {
+    user: &'static str,
+    #[field_by_field(redact)]
+    cookie: &'static str,
+}
+
+fn events() -> Vec<Event> {
+    (0..500)
+        .map(|i| Event { id: i % 7, kind: ["open", "close"][i as usize % 2], timestamp: i as u64 })
+        .collect()
+}
+
+#[test]
+fn found() {
+    assert_contains_field_by_field(&events(), &Event { id: 3, kind: "close", timestamp: 17 });
+}
+
+#[test]
+fn closest_match_has_the_fewest_differences() {
+    let (i, diff) = closest_match(&events(), &Event { id: 3, kind: "close", timestamp: 1000 })
+        .unwrap();
+    assert_eq!(i, 3);
+    assert_eq!(diff.to_string(), "timestamp: 3 != 1000\n");
+
+    assert!(closest_match(&[], &Event { id: 3, kind: "close", timestamp: 1000 }).is_none());
+}
+
+#[test]
+#[should_panic(expected = "
+    Item not found among 500 elements, the closest is [11]:
+        timestamp: 11 != 17
+    closest: Event { id: 4, kind: \"close\", timestamp: 11 }
+     needle: Event { id: 4, kind: \"close\", timestamp: 17 }
+")]
+fn not_found_shows_the_closest_candidate() {
+    assert_contains_field_by_field(&events(), &Event { id: 4, kind: "close", timestamp: 17 });
+}
+
+#[test]
+#[should_panic(expected = "Item not found in an empty collection")]
+fn not_found_in_nothing() {
+    assert_contains_field_by_field(&[], &Event { id: 4, kind: "close", timestamp: 17 });
+}
+
+#[test]
+fn redacted_fields_are_hidden() {
+    let sessions = vec![Session { user: "bob", cookie: "c00kie" }];
+    let needle = Session { user: "eve", cookie: "c00kie" };
+
+    let err = panic::catch_unwind(|| assert_contains_field_by_field(&sessions, &needle))
+        .expect_err("should panic");
+    let message = err.downcast::<String>().expect("panic message should be a String");
+    assert!(message.contains("closest: Session { user: \"bob\", cookie: <redacted, hash "),
+            "{}", message);
+    assert!(!message.contains("c00kie"), "{}", message);
+}
@@ -0,0 +1,92 @@
+//! Test crate for `#[field_by_field(align)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+#[derive(FieldByField, Debug, Clone)]
+struct Step {
+    name: &'static str,
+    secs: u32,
+}
+
+#[derive(FieldByField, Debug)]
+struct Plan {
+    #[field_by_field(align)]
+    steps: Vec<Step>,
+}
+
+#[derive(FieldByField, Debug)]
+struct Header {
+    name: &'static str,
+    #[field_by_field(redact)]
+    value: &'static str,
+}
+
+#[derive(FieldByField, Debug)]
+struct Request {
+    #[field_by_field(align)]
+    headers: Vec<Header>,
+}
+
+fn steps(names: &[&'static str]) -> Vec<Step> {
+    names.iter().map(|&name| Step { name, secs: 1 }).collect()
+}
+
+fn diff(actual: Vec<Step>, expected: Vec<Step>) -> String {
+    Plan { steps: actual }.diff(&Plan { steps: expected }).to_string()
+}
+
+#[test]
+fn equal_sequences() {
+    assert_eq!(diff(steps(&["a", "b", "c"]), steps(&["a", "b", "c"])), "");
+}
+
+#[test]
+fn an_insertion_is_reported_once() {
+    assert_eq!(diff(steps(&["a", "b", "x", "c", "d", "e"]), steps(&["a", "b", "c", "d", "e"])),
+               "steps[2]: unexpected Step { name: \"x\", secs: 1 }\n");
+}
+
+#[test]
+fn a_removal_is_reported_once() {
+    assert_eq!(diff(steps(&["a", "b", "c", "d", "e"]), steps(&["a", "b", "c", "d", "x", "e"])),
+               "steps~[4]: missing Step { name: \"x\", secs: 1 }\n");
+}
+
+#[test]
+fn modified_elements_show_their_fields() {
+    let mut actual = steps(&["a", "b", "c", "d"]);
+    actual[2].secs = 5;
+    actual.remove(0);
+    let expected = steps(&["a", "b", "c", "d"]);
+
+    assert_eq!(diff(actual, expected), "\
+steps
+  ~[0]: missing Step { name: \"a\", secs: 1 }
+  [1]~[2].secs: 5 != 1
+");
+}
+
+#[test]
+fn replacements_pair_up_in_order() {
+    assert_eq!(diff(steps(&["a", "x", "y", "z", "d"]), steps(&["a", "b", "c", "d"])), "\
+steps
+  [1]~[1].name: \"x\" != \"b\"
+  [2]~[2].name: \"y\" != \"c\"
+  [3]: unexpected Step { name: \"z\", secs: 1 }
+");
+}
+
+#[test]
+fn inserted_elements_hide_redacted_fields() {
+    let actual = Request { headers: vec![Header { name: "Authorization", value: "Bearer t0k" }] };
+    let expected = Request { headers: vec![] };
+
+    let rendered = actual.diff(&expected).to_string();
+    assert!(rendered.contains("unexpected Header { name: \"Authorization\", value: <redacted"),
+            "{}", rendered);
+    assert!(!rendered.contains("t0k"), "{}", rendered);
+}
@@ -1,4 +1,4 @@
-/// Test crate for derive(FieldByField) on a mixed-shape enum
+//! Test crate for derive(FieldByField) on a mixed-shape enum
 
 extern crate field_by_field;
 #[macro_use]
@@ -1,4 +1,4 @@
-/// Test crate for derive(FieldByField) on a struct-like enum
+//! Test crate for derive(FieldByField) on a struct-like enum
 
 extern crate field_by_field;
 #[macro_use]
@@ -1,4 +1,4 @@
-/// Test crate for derive(FieldByField) on a tuple-like enum
+//! Test crate for derive(FieldByField) on a tuple-like enum
 
 extern crate field_by_field;
 #[macro_use]
@@ -0,0 +1,120 @@
+//! Test crate for `#[field_by_field(expectation)]` and matchers
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{any, between, equals, is_none, is_some, matches_regex, satisfies, Expect};
+
+#[derive(FieldByField, Debug)]
+#[field_by_field(expectation)]
+struct Order {
+    id: String,
+    qty: u32,
+    coupon: Option<String>,
+    #[field_by_field(redact)]
+    card: String,
+    #[field_by_field(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+}
+
+fn order() -> Order {
+    Order {
+        id: "ord_123".into(),
+        qty: 3,
+        coupon: None,
+        card: "4242".into(),
+        cache: Vec::new(),
+    }
+}
+
+fn mismatches(expectation: &OrderExpectation, order: &Order) -> Vec<String> {
+    expectation.mismatches(order)
+        .into_iter()
+        .map(|field| format!("{}: {:?} does not {:?}", field.field_name, field.actually,
+                             field.expected))
+        .collect()
+}
+
+#[test]
+fn the_default_expectation_matches_anything() {
+    OrderExpectation::default().assert_matches(&order());
+}
+
+#[test]
+fn matching_fields() {
+    let expectation = OrderExpectation {
+        id: matches_regex("^ord_\\d+$"),
+        qty: between(1, 10),
+        coupon: is_none(),
+        card: satisfies("have 4 digits", |card: &String| card.len() == 4),
+    };
+    expectation.assert_matches(&order());
+}
+
+#[test]
+fn mismatches_say_which_matcher_failed() {
+    let expectation = OrderExpectation {
+        id: matches_regex("^ord_[a-z]+$"),
+        qty: 5.into(),
+        coupon: is_some(),
+        card: equals("1111".to_string()),
+    };
+
+    assert_eq!(mismatches(&expectation, &order()), vec![
+        "id: \"ord_123\" does not match /^ord_[a-z]+$/",
+        "qty: 3 does not equal 5",
+        "coupon: None does not have a value",
+        "card: <redacted, hash 44cf…> does not equal \"1111\"",
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "
+    Item does not match expectation:
+        qty: 3 does not lie between 4 and 10
+    actually: Order { id: \"ord_123\", qty: 3, coupon: None, card: <redacted")]
+fn assert_matches_panics_with_the_mismatches() {
+    OrderExpectation { qty: between(4, 10), ..Default::default() }.assert_matches(&order());
+}
+
+#[test]
+fn regexes() {
+    let check = |pattern: &str, text: &str| matches_regex::<str>(pattern).matches(text);
+
+    assert!(check("^ord_", "ord_1"));
+    assert!(!check("^ord_", "xord_1"));
+    assert!(check("ord_", "xord_1"));
+    assert!(check("^a.c$", "abc"));
+    assert!(!check("^a.c$", "abcd"));
+    assert!(check("^colou?r$", "color"));
+    assert!(check("^colou?r$", "colour"));
+    assert!(check("^a+b*$", "aaa"));
+    assert!(!check("^a+b*$", "bbb"));
+    assert!(check("^[A-Z][a-z_-]*$", "Snake_case-ish"));
+    assert!(check("^[^0-9]+$", "no digits"));
+    assert!(!check("^[^0-9]+$", "d1git"));
+    assert!(check("^\\w+\\s\\w+\\.$", "two words."));
+    assert!(check("", "anything"));
+    assert!(check("^(ord|inv)_\\d{3}$", "inv_042"));
+    assert!(!check("^a.c$", "a\nc"));
+}
+
+#[test]
+fn regexes_take_linear_time() {
+    let text = "a".repeat(10_000);
+    assert!(!matches_regex::<str>("^(a*)*b$").matches(&text));
+}
+
+#[test]
+#[should_panic(expected = "Invalid regex /a(b/")]
+fn invalid_regexes_panic() {
+    let _: Expect<str> = matches_regex("a(b");
+}
+
+#[test]
+fn any_matches_anything() {
+    assert!(any::<u32>().matches(&7));
+    assert!(is_some::<u32>().matches(&Some(7)));
+}
@@ -0,0 +1,72 @@
+//! Test crate for `#[field_by_field(transparent)]` and `#[field_by_field(flatten)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+#[field_by_field(transparent)]
+struct UserId(u64);
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Point(i32, i32);
+
+#[derive(FieldByField, Debug, Clone)]
+struct Audit {
+    created_by: UserId,
+    version: u32,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Document {
+    title: String,
+    #[field_by_field(flatten)]
+    audit: Audit,
+}
+
+fn names(list: Vec<field_by_field::UnequalField>) -> Vec<String> {
+    list.into_iter().map(|ue| ue.field_name).collect()
+}
+
+#[test]
+fn tuple_struct_fields_are_named_by_index() {
+    assert_eq!(names(Point(1, 2).fields_not_equal(&Point(1, 3))), vec!["1"]);
+}
+
+#[test]
+fn transparent_structs_have_no_path_segment() {
+    let not_e = UserId(1).fields_not_equal(&UserId(2));
+
+    assert_eq!(names(not_e), vec![""]);
+}
+
+#[test]
+#[should_panic(expected = "Items are not equal:\n        1 != 2\n")]
+fn transparent_structs_report_the_inner_value() {
+    UserId(1).assert_equal_field_by_field(&UserId(2));
+}
+
+#[test]
+fn flattened_fields_are_reported_as_our_own() {
+    let actual = Document {
+        title: "one".into(),
+        audit: Audit { created_by: UserId(1), version: 1 },
+    };
+    let expected = Document {
+        title: "two".into(),
+        audit: Audit { created_by: UserId(2), version: 1 },
+    };
+
+    assert_eq!(names(actual.fields_not_equal(&expected)), vec!["title", "created_by"]);
+}
+
+#[test]
+fn flattened_fields_dont_need_partial_eq() {
+    let doc = Document {
+        title: "one".into(),
+        audit: Audit { created_by: UserId(1), version: 1 },
+    };
+    doc.assert_equal_field_by_field(&doc.clone());
+}
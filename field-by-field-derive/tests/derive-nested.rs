@@ -0,0 +1,82 @@
+//! Test crate for `#[field_by_field(nested)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+// None of these implement PartialEq
+
+#[derive(FieldByField, Debug, Clone)]
+struct Address {
+    zip: String,
+    city: String,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+#[field_by_field(transparent)]
+struct CustomerId(u32);
+
+#[derive(FieldByField, Debug, Clone)]
+struct Customer {
+    #[field_by_field(nested)]
+    id: CustomerId,
+    #[field_by_field(nested)]
+    address: Address,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Order {
+    #[field_by_field(nested)]
+    customer: Customer,
+    total: u32,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Shipment {
+    Pending,
+    Shipped(#[field_by_field(nested)] Address),
+}
+
+fn order(id: u32, zip: &str, total: u32) -> Order {
+    Order {
+        customer: Customer {
+            id: CustomerId(id),
+            address: Address { zip: zip.into(), city: "Cambridge".into() },
+        },
+        total,
+    }
+}
+
+fn names(list: Vec<field_by_field::UnequalField>) -> Vec<String> {
+    list.into_iter().map(|ue| ue.field_name).collect()
+}
+
+#[test]
+fn nested_fields_are_reported_with_their_path() {
+    let not_e = order(1, "02139", 10).fields_not_equal(&order(2, "02138", 11));
+
+    assert_eq!(names(not_e), vec!["customer.id", "customer.address.zip", "total"]);
+}
+
+#[test]
+fn nested_enum_fields_are_reported_with_their_path() {
+    let address = |zip: &str| Address { zip: zip.into(), city: "Boston".into() };
+    let not_e = Shipment::Shipped(address("02139"))
+        .fields_not_equal(&Shipment::Shipped(address("02138")));
+
+    assert_eq!(names(not_e), vec!["Shipment::Shipped.0.zip"]);
+    Shipment::Pending.assert_equal_field_by_field(&Shipment::Pending);
+}
+
+#[test]
+fn nested_types_dont_need_partial_eq() {
+    order(1, "02139", 10).assert_equal_field_by_field(&order(1, "02139", 10));
+}
+
+#[test]
+#[should_panic(expected = "customer.address.zip: \"02139\" != \"02138\"")]
+fn assert_catches_nested_differences() {
+    order(1, "02139", 10).assert_equal_field_by_field(&order(1, "02138", 10));
+}
@@ -0,0 +1,145 @@
+//! Test crate for fields whose types don't implement Debug
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use field_by_field::{CompareOptions, EqualFieldByField};
+
+/// A third-party handle that only implements PartialEq
+#[derive(PartialEq)]
+struct OpaqueHandle(u32);
+
+#[derive(FieldByField)]
+struct Session {
+    id: u32,
+    handle: OpaqueHandle,
+}
+
+#[derive(FieldByField)]
+struct Pool {
+    handles: Vec<OpaqueHandle>,
+    #[field_by_field(unordered)]
+    spares: Vec<OpaqueHandle>,
+    by_name: HashMap<&'static str, OpaqueHandle>,
+}
+
+/// A map key that only implements what `HashMap` needs
+#[derive(PartialEq, Eq, Hash)]
+struct OpaqueKey(u32);
+
+#[derive(FieldByField)]
+struct Index {
+    by_key: HashMap<OpaqueKey, u32>,
+}
+
+#[derive(FieldByField)]
+enum Resource {
+    Handle(OpaqueHandle),
+    Missing,
+}
+
+/// Generic, but without a `T: Debug` bound
+#[derive(FieldByField, Debug)]
+struct Wrap<T: PartialEq> {
+    x: T,
+}
+
+#[derive(FieldByField, Debug)]
+struct DebugWrap<T: PartialEq + Debug> {
+    x: T,
+}
+
+#[test]
+fn fields_without_debug_are_still_compared() {
+    let actual = Session { id: 1, handle: OpaqueHandle(1) };
+    let expected = Session { id: 1, handle: OpaqueHandle(2) };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name, "handle");
+    assert_eq!(format!("{:?}", not_e[0].actually),
+               "<value without Debug, type = derive_no_debug::OpaqueHandle>");
+}
+
+#[test]
+fn fields_with_debug_still_use_it() {
+    let actual = Session { id: 1, handle: OpaqueHandle(1) };
+    let expected = Session { id: 2, handle: OpaqueHandle(1) };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(format!("{:?} != {:?}", not_e[0].actually, not_e[0].expected), "1 != 2");
+}
+
+#[test]
+#[should_panic(expected = "actually: <value without Debug, type = derive_no_debug::Session>")]
+fn assert_works_without_debug() {
+    let actual = Session { id: 1, handle: OpaqueHandle(1) };
+    let expected = Session { id: 1, handle: OpaqueHandle(2) };
+    actual.assert_equal_field_by_field(&expected);
+}
+
+#[test]
+fn enums_without_debug_or_clone_are_compared() {
+    let not_e = Resource::Handle(OpaqueHandle(1)).fields_not_equal(&Resource::Missing);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(format!("{:?}", not_e[0].actually),
+               "<value without Debug, type = derive_no_debug::Resource>");
+    Resource::Missing.assert_equal_field_by_field(&Resource::Missing);
+}
+
+#[test]
+fn collections_without_debug_work_with_runtime_options() {
+    let actual = Pool {
+        handles: vec![OpaqueHandle(1), OpaqueHandle(2)],
+        spares: vec![OpaqueHandle(3)],
+        by_name: vec![("a", OpaqueHandle(1))].into_iter().collect(),
+    };
+    let expected = Pool {
+        handles: vec![OpaqueHandle(3)],
+        spares: vec![OpaqueHandle(4)],
+        by_name: vec![("b", OpaqueHandle(1))].into_iter().collect(),
+    };
+
+    let not_e = actual.fields_not_equal_with(&expected, &CompareOptions::new().subset());
+
+    let rendered = not_e.iter()
+        .map(|field| format!("{}: {:?}", field.field_name, field.expected))
+        .collect::<Vec<_>>();
+    assert_eq!(rendered, vec![
+        "handles: [<value without Debug, type = derive_no_debug::OpaqueHandle>]",
+        "spares: [<value without Debug, type = derive_no_debug::OpaqueHandle>]",
+        "by_name[\"b\"]: <value without Debug, type = derive_no_debug::OpaqueHandle>",
+    ]);
+}
+
+#[test]
+fn map_keys_without_debug_are_shown_as_placeholders() {
+    let actual = Index { by_key: vec![(OpaqueKey(1), 1)].into_iter().collect() };
+    let expected = Index { by_key: vec![(OpaqueKey(1), 2)].into_iter().collect() };
+
+    let not_e = actual.fields_not_equal_with(&expected, &CompareOptions::new().subset());
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name,
+               "by_key[<value without Debug, type = derive_no_debug::OpaqueKey>]");
+    assert_eq!(actual.fields_not_equal(&expected)[0].field_name, "by_key");
+}
+
+#[test]
+fn generic_fields_need_a_debug_bound() {
+    let not_e = Wrap { x: 1 }.fields_not_equal(&Wrap { x: 2 });
+    assert_eq!(format!("{:?}", not_e[0].actually), "<value without Debug, type = i32>");
+    assert_eq!(format!("{:?}", Wrap { x: 1 }.debug_dump()),
+               "<value without Debug, type = derive_no_debug::Wrap<i32>>");
+
+    let not_e = DebugWrap { x: 1 }.fields_not_equal(&DebugWrap { x: 2 });
+    assert_eq!(format!("{:?}", not_e[0].actually), "1");
+    assert_eq!(format!("{:?}", DebugWrap { x: 1 }.debug_dump()), "DebugWrap { x: 1 }");
+}
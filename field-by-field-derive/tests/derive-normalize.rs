@@ -0,0 +1,85 @@
+//! Test crate for `#[field_by_field(normalize = "...")]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+fn lowercase(s: &str) -> String {
+    s.trim().to_lowercase()
+}
+
+fn sorted(v: &[u8]) -> Vec<u8> {
+    let mut v = v.to_vec();
+    v.sort();
+    v
+}
+
+#[derive(FieldByField, Debug)]
+struct User {
+    #[field_by_field(normalize = "lowercase")]
+    email: String,
+    #[field_by_field(normalize = "sorted")]
+    roles: Vec<u8>,
+    #[field_by_field(normalize = "lowercase", redact)]
+    token: String,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Contact {
+    Email(#[field_by_field(normalize = "lowercase")] String),
+}
+
+fn user(email: &str, roles: Vec<u8>) -> User {
+    User { email: email.into(), roles, token: "abc".into() }
+}
+
+#[test]
+fn normalized_values_that_are_equal_are_not_reported() {
+    let actual = user(" Bob@Example.com", vec![3, 1, 2]);
+    let expected = user("bob@example.com", vec![1, 2, 3]);
+
+    assert_eq!(actual.fields_not_equal(&expected).len(), 0);
+    actual.assert_equal_field_by_field(&expected);
+}
+
+#[test]
+fn reports_show_normalized_and_raw_values() {
+    let actual = user("Bob@Example.com ", vec![1]);
+    let expected = user("alice@example.com", vec![1]);
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name, "email");
+    assert_eq!(format!("{:?}", not_e[0].actually),
+               r#""bob@example.com" (normalized from "Bob@Example.com ")"#);
+    assert_eq!(format!("{:?}", not_e[0].expected),
+               r#""alice@example.com" (normalized from "alice@example.com")"#);
+}
+
+#[test]
+fn redacted_normalized_values_stay_hidden() {
+    let actual = User { token: "SECRET".into(), ..user("bob@example.com", vec![]) };
+    let expected = User { token: "other".into(), ..user("bob@example.com", vec![]) };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    let actually = format!("{:?}", not_e[0].actually);
+    assert!(!actually.to_lowercase().contains("secret"), "{}", actually);
+}
+
+#[test]
+fn enum_fields_can_be_normalized() {
+    let actual = Contact::Email("BOB@example.com".into());
+    let expected = Contact::Email("bob@example.com".into());
+    actual.assert_equal_field_by_field(&expected);
+}
+
+#[test]
+#[should_panic(expected = "roles: [1, 2] (normalized from [2, 1]) != [1, 3] (normalized from [1, 3])")]
+fn assert_catches_differences_after_normalizing() {
+    user("bob@example.com", vec![2, 1]).assert_equal_field_by_field(&user("bob@example.com", vec![1, 3]));
+}
@@ -0,0 +1,104 @@
+//! Test crate for `#[field_by_field(unordered, nested)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{min_cost_assignment, EqualFieldByField};
+
+#[derive(FieldByField, Debug, Clone)]
+struct Line {
+    sku: &'static str,
+    qty: u32,
+    price: u32,
+}
+
+#[derive(FieldByField, Debug)]
+struct Order {
+    #[field_by_field(unordered, nested)]
+    lines: Vec<Line>,
+}
+
+#[derive(FieldByField, Debug)]
+struct Key {
+    name: &'static str,
+    #[field_by_field(redact)]
+    secret: &'static str,
+}
+
+#[derive(FieldByField, Debug)]
+struct Keyring {
+    #[field_by_field(unordered, nested)]
+    keys: Vec<Key>,
+}
+
+fn line(sku: &'static str, qty: u32, price: u32) -> Line {
+    Line { sku, qty, price }
+}
+
+fn diff(actual: Vec<Line>, expected: Vec<Line>) -> String {
+    Order { lines: actual }.diff(&Order { lines: expected }).to_string()
+}
+
+#[test]
+fn order_is_ignored() {
+    let lines = vec![line("a", 1, 1), line("b", 2, 2), line("a", 1, 1)];
+    let reversed = lines.iter().rev().cloned().collect();
+
+    assert_eq!(diff(lines, reversed), "");
+}
+
+#[test]
+fn closest_elements_are_paired() {
+    let actual = vec![line("a", 1, 10), line("b", 2, 20), line("c", 3, 30)];
+    let expected = vec![line("c", 3, 30), line("b", 9, 20), line("a", 1, 10)];
+
+    assert_eq!(diff(actual, expected), "lines[1]~[1].qty: 2 != 9\n");
+}
+
+#[test]
+fn each_side_has_one_slightly_wrong_element() {
+    let actual = vec![line("a", 1, 10), line("b", 2, 21), line("c", 4, 30)];
+    let expected = vec![line("c", 3, 30), line("b", 2, 20), line("a", 1, 10)];
+
+    assert_eq!(diff(actual, expected), "\
+lines
+  [1]~[1].price: 21 != 20
+  [2]~[0].qty: 4 != 3
+");
+}
+
+#[test]
+fn leftover_elements_are_unexpected_or_missing() {
+    assert_eq!(diff(vec![line("a", 1, 1), line("b", 2, 2)], vec![line("b", 2, 3)]), "\
+lines
+  [0]: unexpected Line { sku: \"a\", qty: 1, price: 1 }
+  [1]~[0].price: 2 != 3
+");
+    assert_eq!(diff(vec![], vec![line("b", 2, 3)]),
+               "lines~[0]: missing Line { sku: \"b\", qty: 2, price: 3 }\n");
+}
+
+#[test]
+fn leftover_elements_hide_redacted_fields() {
+    let actual = Keyring { keys: vec![Key { name: "deploy", secret: "xyzzy" }] };
+    let expected = Keyring { keys: vec![] };
+
+    let rendered = actual.diff(&expected).to_string();
+    assert!(rendered.starts_with("keys[0]: unexpected Key { name: \"deploy\", secret: <redacted"),
+            "{}", rendered);
+    assert!(!rendered.contains("xyzzy"), "{}", rendered);
+}
+
+#[test]
+fn assignment_is_optimal() {
+    // Greedily taking the cheapest cell first (0 at [0][0]) costs 0 + 10 = 10
+    let costs = vec![vec![0, 1], vec![1, 10]];
+    assert_eq!(min_cost_assignment(&costs), vec![Some(1), Some(0)]);
+
+    let wide = vec![vec![5, 1, 3]];
+    assert_eq!(min_cost_assignment(&wide), vec![Some(1)]);
+
+    let tall = vec![vec![5], vec![1], vec![3]];
+    assert_eq!(min_cost_assignment(&tall), vec![None, Some(0), None]);
+}
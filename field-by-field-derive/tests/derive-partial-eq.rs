@@ -0,0 +1,70 @@
+//! Test crate for `#[field_by_field(derive_partial_eq)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::marker::PhantomData;
+
+use field_by_field::EqualFieldByField;
+
+fn trimmed(s: &str) -> &str {
+    s.trim()
+}
+
+#[derive(FieldByField, Debug, Clone)]
+#[field_by_field(derive_partial_eq)]
+struct Account {
+    id: u32,
+    #[field_by_field(normalize = "trimmed")]
+    name: String,
+    #[field_by_field(skip)]
+    last_seen: u64,
+    kind: PhantomData<u8>,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+#[field_by_field(derive_partial_eq)]
+struct Wrapper {
+    #[field_by_field(nested)]
+    account: Account,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+#[field_by_field(derive_partial_eq)]
+enum State {
+    Open(#[field_by_field(normalize = "trimmed")] String),
+    Closed,
+}
+
+fn account(id: u32, name: &str, last_seen: u64) -> Account {
+    Account { id, name: name.into(), last_seen, kind: PhantomData }
+}
+
+#[test]
+fn eq_follows_field_by_field_rules() {
+    let (actual, expected) = (account(1, " bob ", 10), account(1, "bob", 20));
+    assert!(actual.last_seen != expected.last_seen);
+    assert_eq!(actual, expected);
+    assert!(account(1, "bob", 10) != account(2, "bob", 10));
+}
+
+#[test]
+fn eq_agrees_with_fields_not_equal() {
+    let pairs = vec![
+        (account(1, "bob", 10), account(1, "bob ", 11)),
+        (account(1, "bob", 10), account(1, "alice", 10)),
+        (account(1, "bob", 10), account(3, "bob", 10)),
+    ];
+    for (a, b) in pairs {
+        assert_eq!(a == b, a.fields_not_equal(&b).is_empty());
+    }
+}
+
+#[test]
+fn nested_and_enum_types_get_partial_eq() {
+    assert_eq!(Wrapper { account: account(1, "bob", 1) },
+               Wrapper { account: account(1, "bob", 2) });
+    assert_eq!(State::Open("x ".into()), State::Open("x".into()));
+    assert!(State::Open("x".into()) != State::Closed);
+}
@@ -0,0 +1,129 @@
+//! Test crate for `#[field_by_field(redact)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::panic;
+
+use field_by_field::EqualFieldByField;
+
+#[derive(FieldByField, Debug, Clone)]
+struct Login {
+    user: String,
+    #[field_by_field(redact)]
+    password: String,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Credential {
+    Token(#[field_by_field(redact)] String),
+    Basic { user: String, #[field_by_field(redact)] password: String },
+}
+
+#[derive(FieldByField, Debug)]
+struct Account {
+    id: u32,
+    #[field_by_field(nested)]
+    login: Login,
+    #[field_by_field(nested)]
+    previous: Vec<Login>,
+}
+
+#[derive(FieldByField, Debug)]
+struct Session {
+    #[field_by_field(flatten)]
+    login: Login,
+    open: bool,
+}
+
+fn panic_message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let err = panic::catch_unwind(f).expect_err("expected a panic");
+    err.downcast::<String>().map(|s| *s).expect("panic message should be a String")
+}
+
+#[test]
+fn redacted_fields_are_still_compared() {
+    let actual = Login { user: "me".into(), password: "hunter2".into() };
+    let expected = Login { user: "me".into(), password: "hunter3".into() };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name, "password");
+    let actually = format!("{:?}", not_e[0].actually);
+    let expected = format!("{:?}", not_e[0].expected);
+    assert!(actually.starts_with("<redacted, hash "), "{}", actually);
+    assert!(!actually.contains("hunter2"));
+    assert!(!expected.contains("hunter3"));
+    assert!(actually != expected);
+}
+
+#[test]
+fn equal_values_redact_to_the_same_hash() {
+    assert_eq!(format!("{:?}", field_by_field::Redacted::new("hunter2")),
+               format!("{:?}", field_by_field::Redacted::new("hunter2")));
+}
+
+#[test]
+fn assert_message_does_not_contain_secrets() {
+    let msg = panic_message(|| {
+        let actual = Login { user: "me".into(), password: "hunter2".into() };
+        let expected = Login { user: "you".into(), password: "hunter3".into() };
+        actual.assert_equal_field_by_field(&expected);
+    });
+
+    assert!(msg.contains("user: \"me\" != \"you\""), "{}", msg);
+    assert!(msg.contains("actually: Login { user: \"me\", password: <redacted, hash "), "{}", msg);
+    assert!(!msg.contains("hunter"), "{}", msg);
+}
+
+#[test]
+fn assert_message_does_not_contain_secrets_in_enums() {
+    let msg = panic_message(|| {
+        let actual = Credential::Token("s3cret".into());
+        let expected = Credential::Basic { user: "me".into(), password: "hunter2".into() };
+        actual.assert_equal_field_by_field(&expected);
+    });
+
+    assert!(msg.contains("actually: Token(<redacted, hash "), "{}", msg);
+    assert!(msg.contains("expected: Basic { user: \"me\", password: <redacted, hash "), "{}", msg);
+    assert!(!msg.contains("s3cret"), "{}", msg);
+    assert!(!msg.contains("hunter2"), "{}", msg);
+}
+
+#[test]
+fn assert_message_does_not_contain_secrets_of_nested_fields() {
+    let msg = panic_message(|| {
+        let login = Login { user: "me".into(), password: "hunter2".into() };
+        let actual = Account { id: 1, login: login.clone(), previous: vec![login.clone()] };
+        let expected = Account { id: 2, login: login.clone(), previous: vec![login] };
+        actual.assert_equal_field_by_field(&expected);
+    });
+
+    assert!(msg.contains("actually: Account { id: 1, login: Login { user: \"me\", \
+                          password: <redacted, hash "), "{}", msg);
+    assert!(msg.contains("previous: [Login { user: \"me\", password: <redacted, hash "),
+            "{}", msg);
+    assert!(!msg.contains("hunter2"), "{}", msg);
+}
+
+#[test]
+fn assert_message_does_not_contain_secrets_of_flattened_fields() {
+    let msg = panic_message(|| {
+        let login = Login { user: "me".into(), password: "hunter2".into() };
+        let actual = Session { login: login.clone(), open: true };
+        let expected = Session { login, open: false };
+        actual.assert_equal_field_by_field(&expected);
+    });
+
+    assert!(msg.contains("actually: Session { login: Login { user: \"me\", \
+                          password: <redacted, hash "), "{}", msg);
+    assert!(!msg.contains("hunter2"), "{}", msg);
+}
+
+#[test]
+fn assert_allows_identical() {
+    let login = Login { user: "me".into(), password: "hunter2".into() };
+    login.assert_equal_field_by_field(&login.clone());
+}
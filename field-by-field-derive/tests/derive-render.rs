@@ -0,0 +1,103 @@
+//! Test crate for `#[field_by_field(display)]` and `#[field_by_field(debug_with = "...")]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+
+use field_by_field::EqualFieldByField;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Handle(u32);
+
+fn fmt_handle(handle: &Handle, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "handle#{:x}", handle.0)
+}
+
+#[derive(FieldByField, Debug)]
+struct Connection {
+    #[field_by_field(display)]
+    addr: IpAddr,
+    #[field_by_field(debug_with = "fmt_handle")]
+    handle: Handle,
+    name: String,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Peer {
+    Known(#[field_by_field(display)] IpAddr),
+    Opaque { #[field_by_field(debug_with = "fmt_handle")] handle: Handle },
+}
+
+fn rendered(field: &field_by_field::UnequalField) -> (String, String) {
+    (format!("{:?}", field.actually), format!("{:?}", field.expected))
+}
+
+#[test]
+fn display_fields_are_rendered_with_display() {
+    let actual = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        handle: Handle(1),
+        name: "db".into(),
+    };
+    let expected = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+        handle: Handle(1),
+        name: "db".into(),
+    };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(rendered(&not_e[0]), ("10.0.0.1".to_string(), "10.0.0.2".to_string()));
+}
+
+#[test]
+fn debug_with_fields_are_rendered_with_the_function() {
+    let actual = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        handle: Handle(0xff),
+        name: "db".into(),
+    };
+    let expected = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        handle: Handle(0xa),
+        name: "cache".into(),
+    };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 2);
+    assert_eq!(not_e[0].field_name, "handle");
+    assert_eq!(rendered(&not_e[0]), ("handle#ff".to_string(), "handle#a".to_string()));
+    assert_eq!(rendered(&not_e[1]), ("\"db\"".to_string(), "\"cache\"".to_string()));
+}
+
+#[test]
+fn enum_fields_can_be_rendered() {
+    let known = |last| Peer::Known(IpAddr::V4(Ipv4Addr::new(192, 168, 0, last)));
+    let not_e = known(1).fields_not_equal(&known(2));
+    assert_eq!(rendered(&not_e[0]), ("192.168.0.1".to_string(), "192.168.0.2".to_string()));
+
+    let opaque = |h| Peer::Opaque { handle: Handle(h) };
+    let not_e = opaque(1).fields_not_equal(&opaque(2));
+    assert_eq!(rendered(&not_e[0]), ("handle#1".to_string(), "handle#2".to_string()));
+}
+
+#[test]
+#[should_panic(expected = "addr: 10.0.0.1 != 10.0.0.2")]
+fn assert_uses_custom_rendering() {
+    let actual = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        handle: Handle(1),
+        name: "db".into(),
+    };
+    let expected = Connection {
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+        handle: Handle(1),
+        name: "db".into(),
+    };
+    actual.assert_equal_field_by_field(&expected);
+}
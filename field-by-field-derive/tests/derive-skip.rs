@@ -0,0 +1,72 @@
+//! Test crate for skipped fields, including automatically skipped markers
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::marker::{PhantomData, PhantomPinned};
+
+use field_by_field::EqualFieldByField;
+
+/// Deliberately implements none of `PartialEq`, `Clone` or `Debug`
+struct Opaque;
+
+/// Comparing `kind` would need `'a: 'static` to box it up
+#[derive(FieldByField, Debug)]
+struct Typed<'a> {
+    id: u32,
+    kind: PhantomData<&'a Opaque>,
+    _pin: PhantomPinned,
+    #[field_by_field(skip)]
+    cache: Vec<u32>,
+}
+
+#[derive(FieldByField, Debug, PartialEq, Clone)]
+struct Included {
+    id: u32,
+    #[field_by_field(include)]
+    marker: (),
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Tagged {
+    Tuple(u32, PhantomData<Opaque>),
+    Struct { id: u32, kind: PhantomData<Opaque> },
+}
+
+fn typed<'a>(id: u32, cache: Vec<u32>) -> Typed<'a> {
+    Typed { id, kind: PhantomData, _pin: PhantomPinned, cache }
+}
+
+#[test]
+fn markers_and_skipped_fields_are_not_compared() {
+    let (actual, expected) = (typed(1, vec![1]), typed(1, vec![2]));
+    assert!(actual.cache != expected.cache);
+
+    actual.assert_equal_field_by_field(&expected);
+}
+
+#[test]
+fn other_fields_are_still_compared() {
+    let not_e = typed(1, vec![]).fields_not_equal(&typed(2, vec![]));
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name, "id");
+}
+
+#[test]
+fn included_markers_are_compared() {
+    let included = Included { id: 1, marker: () };
+    included.assert_equal_field_by_field(&included.clone());
+}
+
+#[test]
+fn enum_markers_are_not_compared() {
+    let not_e = Tagged::Tuple(1, PhantomData)
+        .fields_not_equal(&Tagged::Tuple(2, PhantomData));
+    assert_eq!(not_e.len(), 1);
+
+    let not_e = Tagged::Struct { id: 1, kind: PhantomData }
+        .fields_not_equal(&Tagged::Struct { id: 2, kind: PhantomData });
+    assert_eq!(not_e.len(), 1);
+}
@@ -1,4 +1,4 @@
-/// Test crate for derive(FieldByField).
+//! Test crate for derive(FieldByField).
 
 extern crate field_by_field;
 #[macro_use]
@@ -0,0 +1,69 @@
+//! Test crate for `#[field_by_field(unordered)]`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::EqualFieldByField;
+
+/// Deliberately neither `Ord` nor `Hash`
+#[derive(Debug, Clone, PartialEq)]
+struct Tag(f32);
+
+#[derive(FieldByField, Debug)]
+struct Post<'a> {
+    #[field_by_field(unordered)]
+    tags: Vec<Tag>,
+    #[field_by_field(unordered)]
+    authors: &'a [&'a str],
+}
+
+#[derive(FieldByField, Debug, Clone)]
+enum Group {
+    Members(#[field_by_field(unordered)] Vec<u8>),
+}
+
+#[test]
+fn order_is_ignored() {
+    let actual = Post { tags: vec![Tag(1.0), Tag(2.0), Tag(1.0)], authors: &["a", "b"] };
+    let expected = Post { tags: vec![Tag(1.0), Tag(1.0), Tag(2.0)], authors: &["b", "a"] };
+
+    assert_eq!(actual.fields_not_equal(&expected).len(), 0);
+    actual.assert_equal_field_by_field(&expected);
+}
+
+#[test]
+fn multiplicities_matter() {
+    let actual = Post { tags: vec![Tag(1.0), Tag(1.0), Tag(1.0), Tag(2.0)], authors: &["a"] };
+    let expected = Post { tags: vec![Tag(1.0), Tag(3.0)], authors: &["a"] };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(not_e[0].field_name, "tags");
+    assert_eq!(format!("{:?}", not_e[0].actually), "only in actual: [Tag(1.0) ×2, Tag(2.0)]");
+    assert_eq!(format!("{:?}", not_e[0].expected), "only in expected: [Tag(3.0)]");
+}
+
+#[test]
+fn slices_are_supported() {
+    let actual = Post { tags: vec![], authors: &["a", "b"] };
+    let expected = Post { tags: vec![], authors: &["b"] };
+
+    let not_e = actual.fields_not_equal(&expected);
+
+    assert_eq!(not_e.len(), 1);
+    assert_eq!(format!("{:?}", not_e[0].actually), r#"only in actual: ["a"]"#);
+    assert_eq!(format!("{:?}", not_e[0].expected), "only in expected: []");
+}
+
+#[test]
+fn enum_fields_can_be_unordered() {
+    Group::Members(vec![1, 2, 3]).assert_equal_field_by_field(&Group::Members(vec![3, 2, 1]));
+}
+
+#[test]
+#[should_panic(expected = "Group::Members.0: only in actual: [4] != only in expected: [1]")]
+fn assert_catches_differences() {
+    Group::Members(vec![4, 2, 3]).assert_equal_field_by_field(&Group::Members(vec![3, 2, 1]));
+}
@@ -0,0 +1,165 @@
+//! Test crate for the `Diff` tree built from `fields_not_equal`
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{Diff, DiffKind, EqualFieldByField};
+
+#[derive(FieldByField, Debug, Clone)]
+struct Address {
+    zip: String,
+    city: String,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Customer {
+    name: String,
+    #[field_by_field(nested)]
+    address: Address,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Order {
+    #[field_by_field(nested)]
+    customer: Customer,
+    total: u32,
+}
+
+fn order(name: &str, zip: &str, city: &str, total: u32) -> Order {
+    Order {
+        customer: Customer {
+            name: name.into(),
+            address: Address { zip: zip.into(), city: city.into() },
+        },
+        total,
+    }
+}
+
+fn paths(diff: &Diff) -> Vec<String> {
+    diff.leaves().into_iter().map(|leaf| leaf.path).collect()
+}
+
+fn diff() -> Diff {
+    order("bob", "02139", "Cambridge", 1).diff(&order("alice", "02138", "Boston", 2))
+}
+
+#[test]
+fn path_segments() {
+    use field_by_field::path::segments;
+
+    assert_eq!(segments("customer.address.zip"), vec!["customer", "address", "zip"]);
+    assert_eq!(segments("lines[2].qty"), vec!["lines", "[2]", "qty"]);
+    assert_eq!(segments("headers[\"a.b\"]"), vec!["headers", "[\"a.b\"]"]);
+    assert_eq!(segments(""), Vec::<&str>::new());
+}
+
+#[test]
+fn siblings_are_grouped_under_their_parent() {
+    let diff = diff();
+
+    match diff {
+        Diff::Node { ref segment, ref children } => {
+            assert_eq!(segment, "");
+            assert_eq!(children.len(), 2);
+            match children[0] {
+                Diff::Node { ref segment, ref children } => {
+                    assert_eq!(segment, "customer");
+                    assert_eq!(children.len(), 2);
+                }
+                ref other => panic!("expected a customer node, got {:?}", other),
+            }
+        }
+        ref other => panic!("expected a root node, got {:?}", other),
+    }
+    assert_eq!(diff.leaf_count(), 4);
+    assert_eq!(paths(&diff), vec!["customer.name", "customer.address.zip",
+                                  "customer.address.city", "total"]);
+}
+
+#[test]
+fn flat_list_round_trips() {
+    let fields = diff().into_fields();
+
+    let names: Vec<_> = fields.iter().map(|f| f.field_name.as_str()).collect();
+    assert_eq!(names, vec!["customer.name", "customer.address.zip", "customer.address.city",
+                           "total"]);
+    assert!(fields.iter().all(|f| f.kind == DiffKind::Changed));
+    assert_eq!(format!("{:?}", fields[3].actually), "1");
+}
+
+#[test]
+fn retain_filters_leaves_and_drops_empty_nodes() {
+    let mut diff = diff();
+
+    diff.retain(|path, _| path.ends_with("zip"));
+
+    assert_eq!(paths(&diff), vec!["customer.address.zip"]);
+    diff.retain(|_, kind| kind != DiffKind::Changed);
+    assert!(diff.is_empty());
+    match diff {
+        Diff::Node { ref children, .. } => assert!(children.is_empty()),
+        ref other => panic!("expected a root node, got {:?}", other),
+    }
+}
+
+#[test]
+fn prune_removes_subtrees() {
+    let mut diff = diff();
+
+    diff.prune(|path| path == "customer.address");
+
+    assert_eq!(paths(&diff), vec!["customer.name", "total"]);
+}
+
+#[test]
+fn walk_visits_parents_first() {
+    let diff = diff();
+    let mut nodes = Vec::new();
+
+    diff.walk(&mut |path, node| if let Diff::Node { .. } = *node {
+        nodes.push(path.to_string());
+    });
+
+    assert_eq!(nodes, vec!["", "customer", "customer.name", "customer.address",
+                           "customer.address.zip", "customer.address.city", "total"]);
+}
+
+#[test]
+fn equal_values_have_an_empty_diff() {
+    let order = order("bob", "02139", "Cambridge", 1);
+    assert!(order.diff(&order.clone()).is_empty());
+}
+
+#[test]
+fn render_groups_siblings_and_collapses_chains() {
+    let diff = order("bob", "02139", "Cambridge", 1).diff(&order("bob", "02138", "Boston", 2));
+
+    assert_eq!(diff.to_string(),
+               "customer.address\n\
+               \x20 zip: \"02139\" != \"02138\"\n\
+               \x20 city: \"Cambridge\" != \"Boston\"\n\
+               total: 1 != 2\n");
+}
+
+#[test]
+fn render_indents_every_line() {
+    let diff = order("bob", "02139", "Cambridge", 1).diff(&order("alice", "02138", "Boston", 1));
+
+    assert_eq!(diff.render(4),
+               "    customer\n\
+               \x20     name: \"bob\" != \"alice\"\n\
+               \x20     address\n\
+               \x20       zip: \"02139\" != \"02138\"\n\
+               \x20       city: \"Cambridge\" != \"Boston\"\n");
+}
+
+#[test]
+#[should_panic(expected = "Items are not equal:
+        customer.address.zip: \"02139\" != \"02138\"
+        total: 1 != 2
+    actually: Order")]
+fn assert_renders_a_tree() {
+    order("bob", "02139", "Cambridge", 1)
+        .assert_equal_field_by_field(&order("bob", "02138", "Cambridge", 2));
+}
@@ -0,0 +1,94 @@
+//! Test crate for comparing records by key
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use field_by_field::{assert_records_eq_by_key, records_diff_by_key};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Status {
+    Paid,
+    Pending,
+}
+
+#[derive(FieldByField, Debug, Clone)]
+struct Invoice {
+    id: u32,
+    status: Status,
+    cents: u64,
+}
+
+fn invoice(id: u32, status: Status, cents: u64) -> Invoice {
+    Invoice { id, status, cents }
+}
+
+#[test]
+fn order_does_not_matter() {
+    let actual = (0..1000).map(|id| invoice(id, Status::Paid, 100)).collect::<Vec<_>>();
+    let expected = actual.iter().rev().cloned().collect::<Vec<_>>();
+
+    assert_records_eq_by_key(&actual, &expected, |invoice| invoice.id);
+}
+
+#[test]
+fn many_differing_records() {
+    let actual = (0..50_000).map(|id| invoice(id, Status::Paid, 100)).collect::<Vec<_>>();
+    let expected = (0..50_000).map(|id| invoice(id, Status::Pending, 100)).collect::<Vec<_>>();
+
+    let diff = records_diff_by_key(&actual, &expected, |invoice| invoice.id);
+    assert_eq!(diff.leaf_count(), 50_000);
+}
+
+#[test]
+fn records_are_reported_by_key() {
+    let actual = vec![invoice(42, Status::Paid, 100), invoice(9, Status::Paid, 5)];
+    let expected = vec![invoice(7, Status::Paid, 1), invoice(42, Status::Pending, 100)];
+
+    let diff = records_diff_by_key(&actual, &expected, |invoice| invoice.id);
+    assert_eq!(diff.to_string(), "\
+[7]: missing Invoice { id: 7, status: Paid, cents: 1 }
+[42].status: Paid != Pending
+[9]: unexpected Invoice { id: 9, status: Paid, cents: 5 }
+");
+}
+
+#[test]
+#[should_panic(expected = "
+    Records are not equal:
+        [\"inv-2\"].cents: 2 != 3
+    0 missing, 0 unexpected, 1 changed (of 2 actual and 2 expected)
+")]
+fn assert_summarizes_the_differences() {
+    let actual = vec![invoice(1, Status::Paid, 1), invoice(2, Status::Paid, 2)];
+    let expected = vec![invoice(2, Status::Paid, 3), invoice(1, Status::Paid, 1)];
+
+    assert_records_eq_by_key(&actual, &expected, |invoice| format!("inv-{}", invoice.id));
+}
+
+#[test]
+#[should_panic(expected = "More than one expected record has the key 1")]
+fn duplicate_keys_are_rejected() {
+    let actual = vec![invoice(1, Status::Paid, 1)];
+    let expected = vec![invoice(1, Status::Paid, 1), invoice(1, Status::Pending, 1)];
+
+    assert_records_eq_by_key(&actual, &expected, |invoice| invoice.id);
+}
+
+#[derive(FieldByField, Debug)]
+struct Account {
+    id: u32,
+    #[field_by_field(redact)]
+    token: &'static str,
+}
+
+#[test]
+fn missing_and_unexpected_records_hide_redacted_fields() {
+    let actual = vec![Account { id: 1, token: "s3cret" }];
+    let expected = vec![Account { id: 2, token: "s3cret" }];
+
+    let rendered = records_diff_by_key(&actual, &expected, |account| account.id).to_string();
+    assert!(rendered.contains("[1]: unexpected Account { id: 1, token: <redacted, hash "),
+            "{}", rendered);
+    assert!(!rendered.contains("s3cret"), "{}", rendered);
+}
@@ -0,0 +1,112 @@
+//! Test crate for `SoftAsserter` and `assert_all!`
+
+#[macro_use]
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::panic;
+
+use field_by_field::SoftAsserter;
+
+#[derive(FieldByField, Debug)]
+struct Cart {
+    total: u32,
+    items: u32,
+}
+
+#[derive(FieldByField, Debug)]
+struct User {
+    name: &'static str,
+}
+
+#[derive(FieldByField, Debug)]
+struct Login {
+    user: &'static str,
+    #[field_by_field(redact)]
+    password: &'static str,
+}
+
+#[test]
+fn nothing_happens_when_everything_is_equal() {
+    let mut soft = SoftAsserter::new();
+    assert!(soft.check("cart", &Cart { total: 1, items: 1 }, &Cart { total: 1, items: 1 }));
+    assert!(soft.failures().is_empty());
+    soft.finish();
+}
+
+#[test]
+fn failures_are_collected_with_their_labels() {
+    let mut soft = SoftAsserter::new();
+    assert!(!soft.check("cart", &Cart { total: 0, items: 1 }, &Cart { total: 1, items: 1 }));
+    assert!(soft.check("user", &User { name: "bob" }, &User { name: "bob" }));
+    assert!(!soft.check("admin", &User { name: "eve" }, &User { name: "root" }));
+
+    let labels = soft.failures().iter().map(|failure| &failure.label[..]).collect::<Vec<_>>();
+    assert_eq!(labels, vec!["cart", "admin"]);
+    assert_eq!(soft.failures()[0].diff.to_string(), "total: 0 != 1\n");
+
+    let message = panic::catch_unwind(panic::AssertUnwindSafe(|| soft.finish()))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert_eq!(*message, "
+    2 of 3 comparisons were not equal:
+
+    cart:
+        total: 0 != 1
+        actually: Cart { total: 0, items: 1 }
+        expected: Cart { total: 1, items: 1 }
+
+    admin:
+        name: \"eve\" != \"root\"
+        actually: User { name: \"eve\" }
+        expected: User { name: \"root\" }
+");
+}
+
+#[test]
+#[should_panic(expected = "1 of 1 comparisons were not equal")]
+fn dropping_without_finishing_still_fails() {
+    let mut soft = SoftAsserter::new();
+    soft.check("cart", &Cart { total: 0, items: 1 }, &Cart { total: 1, items: 1 });
+}
+
+#[test]
+fn assert_all_passes_when_everything_is_equal() {
+    let cart = Cart { total: 1, items: 2 };
+    let user = User { name: "bob" };
+    assert_all! {
+        cart, Cart { total: 1, items: 2 };
+        user, User { name: "bob" };
+    }
+}
+
+#[test]
+#[should_panic(expected = "
+    cart:
+        items: 2 != 3
+        actually: Cart { total: 1, items: 2 }
+        expected: Cart { total: 1, items: 3 }
+
+    user:
+        name: \"bob\" != \"alice\"")]
+fn assert_all_reports_every_comparison() {
+    let cart = Cart { total: 1, items: 2 };
+    let user = User { name: "bob" };
+    assert_all!(cart, Cart { total: 1, items: 3 }; user, User { name: "alice" });
+}
+
+#[test]
+fn redacted_fields_are_hidden_in_the_report() {
+    let mut soft = SoftAsserter::new();
+    soft.check("login",
+               &Login { user: "bob", password: "hunter2" },
+               &Login { user: "alice", password: "hunter2" });
+
+    let failure = &soft.failures()[0];
+    assert!(failure.actually.starts_with("Login { user: \"bob\", password: <redacted, hash "),
+            "{}", failure.actually);
+    assert!(!failure.expected.contains("hunter2"), "{}", failure.expected);
+    panic::catch_unwind(panic::AssertUnwindSafe(|| soft.finish())).unwrap_err();
+}
@@ -0,0 +1,163 @@
+//! Test crate for subset comparisons, where actual can have extra entries
+
+extern crate field_by_field;
+#[macro_use]
+extern crate field_by_field_derive;
+
+use std::collections::{BTreeMap, HashSet};
+use std::panic;
+
+use field_by_field::{CompareOptions, EqualFieldByField};
+
+#[derive(FieldByField, Debug)]
+struct Body {
+    kind: &'static str,
+    fields: BTreeMap<&'static str, i64>,
+}
+
+#[derive(FieldByField, Debug)]
+struct Response {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    tags: Vec<&'static str>,
+    scopes: HashSet<&'static str>,
+    #[field_by_field(nested)]
+    body: Body,
+}
+
+#[derive(FieldByField, Debug)]
+struct Login {
+    user: &'static str,
+    #[field_by_field(redact)]
+    password: &'static str,
+    groups: Vec<&'static str>,
+}
+
+#[derive(FieldByField, Debug, Clone, PartialEq)]
+struct Item {
+    id: u32,
+}
+
+#[derive(FieldByField, Debug)]
+struct Listing {
+    #[field_by_field(nested)]
+    items: Vec<Item>,
+    #[field_by_field(unordered, nested)]
+    pairs: Vec<Item>,
+    #[field_by_field(align)]
+    lines: Vec<Item>,
+}
+
+fn listing(ids: &[u32]) -> Listing {
+    let items = ids.iter().map(|&id| Item { id }).collect::<Vec<_>>();
+    Listing { items: items.clone(), pairs: items.iter().rev().cloned().collect(), lines: items }
+}
+
+fn response(headers: &[(&str, &str)],
+            tags: Vec<&'static str>,
+            fields: &[(&'static str, i64)])
+-> Response {
+    Response {
+        status: 200,
+        headers: headers.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect(),
+        tags: tags.clone(),
+        scopes: tags.into_iter().collect(),
+        body: Body { kind: "json", fields: fields.iter().cloned().collect() },
+    }
+}
+
+#[test]
+fn extra_entries_in_actual_are_ignored() {
+    let actual = response(&[("etag", "1"), ("date", "today")], vec!["a", "b", "c"],
+                          &[("id", 1), ("new_field", 2)]);
+    let expected = response(&[("etag", "1")], vec!["c", "a"], &[("id", 1)]);
+
+    actual.assert_contains_fields(&expected);
+    assert!(!actual.fields_not_equal(&expected).is_empty());
+}
+
+#[test]
+fn missing_and_different_entries_are_reported_with_paths() {
+    let actual = response(&[("etag", "1")], vec!["a"], &[("id", 1)]);
+    let expected = response(&[("etag", "2"), ("date", "today")], vec!["a", "b"],
+                            &[("id", 2), ("count", 3)]);
+
+    let diff = actual.compare(&expected).subset().diff();
+    assert_eq!(diff.to_string(), "\
+headers
+  [\"date\"]: missing \"today\"
+  [\"etag\"]: \"1\" != \"2\"
+tags: missing [\"b\"]
+scopes: missing [\"b\"]
+body.fields
+  [\"count\"]: missing 3
+  [\"id\"]: 1 != 2
+");
+}
+
+#[test]
+fn fields_that_are_not_collections_still_have_to_be_equal() {
+    let mut actual = response(&[], vec![], &[]);
+    actual.status = 500;
+    actual.body.kind = "html";
+    let expected = response(&[], vec![], &[]);
+
+    let names = actual.fields_not_equal_with(&expected, &CompareOptions::new().subset())
+        .into_iter()
+        .map(|field| field.field_name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["status", "body.kind"]);
+}
+
+#[test]
+#[should_panic(expected = "tags: missing [\"z\"]")]
+fn assert_contains_fields_panics() {
+    response(&[], vec!["a"], &[]).assert_contains_fields(&response(&[], vec!["z"], &[]));
+}
+
+#[test]
+fn assert_contains_fields_hides_redacted_fields() {
+    let actual = Login { user: "bob", password: "hunter2", groups: vec!["dev"] };
+    let expected = Login { user: "bob", password: "hunter2", groups: vec!["ops"] };
+
+    let err = panic::catch_unwind(|| actual.assert_contains_fields(&expected))
+        .expect_err("should panic");
+    let message = err.downcast::<String>().expect("panic message should be a String");
+    assert!(message.contains("password: <redacted, hash "), "{}", message);
+    assert!(!message.contains("hunter2"), "{}", message);
+}
+
+#[test]
+fn keys_with_brackets_and_dots_keep_their_paths() {
+    let actual = response(&[("a]b", "1"), ("c.d", "1")], vec![], &[]);
+    let expected = response(&[("a]b", "2"), ("c.d", "2")], vec![], &[]);
+
+    let diff = actual.compare(&expected).subset().diff();
+    assert_eq!(diff.to_string(), "\
+headers
+  [\"a]b\"]: \"1\" != \"2\"
+  [\"c.d\"]: \"1\" != \"2\"
+");
+    let ignored = actual.compare(&expected).subset().ignoring(&["headers[\"a]b\"]"]).diff();
+    assert_eq!(ignored.to_string(), "headers[\"c.d\"]: \"1\" != \"2\"\n");
+}
+
+#[test]
+fn extra_elements_of_nested_sequences_are_ignored() {
+    let actual = listing(&[1, 2, 3]);
+
+    actual.assert_contains_fields(&listing(&[1, 2]));
+    assert_eq!(actual.fields_not_equal(&listing(&[1, 2])).len(), 3);
+}
+
+#[test]
+fn missing_elements_of_nested_sequences_are_still_reported() {
+    let actual = listing(&[1]);
+    let expected = listing(&[1, 2]);
+
+    let names = actual.fields_not_equal_with(&expected, &CompareOptions::new().subset())
+        .into_iter()
+        .map(|field| field.field_name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["items[1]", "pairs~[0]", "lines~[1]"]);
+}
@@ -0,0 +1,130 @@
+//! Diffing sequences of structs by aligning them, like a text diff
+
+use {DiffKind, EqualFieldByField, Rendered, UnequalField};
+
+/// Compare two sequences by aligning their equal elements
+///
+/// Equal elements are matched up as a longest common subsequence, so one
+/// inserted element is reported once instead of shifting every element after
+/// it. Between those, elements are paired up in order and their differing
+/// fields are reported under both indexes, like `[2]~[3].qty` for `actual[2]`
+/// and `expected[3]`. Anything left over was inserted, reported as unexpected
+/// under its index in actual (`[2]`), or removed, reported as missing under
+/// its index in expected (`~[3]`).
+pub fn aligned_diff<'a, T, A, E>(actual: A, expected: E) -> Vec<UnequalField>
+    where T: EqualFieldByField + 'a,
+          A: IntoIterator<Item = &'a T>,
+          E: IntoIterator<Item = &'a T>
+{
+    let actual: Vec<&T> = actual.into_iter().collect();
+    let expected: Vec<&T> = expected.into_iter().collect();
+    let (n, m) = (actual.len(), expected.len());
+
+    let equal: Vec<Vec<bool>> = actual.iter()
+        .map(|a| expected.iter().map(|e| a.fields_not_equal(e).is_empty()).collect())
+        .collect();
+    // lcs[i][j] is the length of the longest common subsequence of
+    // actual[i..] and expected[j..]
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if equal[i][j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut fields = Vec::new();
+    let (mut inserted, mut removed) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && equal[i][j] {
+            report_gap(&actual, &expected, &mut inserted, &mut removed, &mut fields);
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            removed.push(j);
+            j += 1;
+        } else {
+            inserted.push(i);
+            i += 1;
+        }
+    }
+    report_gap(&actual, &expected, &mut inserted, &mut removed, &mut fields);
+    fields
+}
+
+/// Compare two sequences position by position
+///
+/// `actual[i]` is compared with `expected[i]` by `compare`, which is also
+/// given the pair's `[i]` segment, and its differing fields are reported under
+/// that segment, like `[2].id`. If one side is longer, its extra elements are
+/// reported as unexpected or missing.
+pub fn indexed_diff<'a, T, A, E, F>(actual: A, expected: E, compare: F) -> Vec<UnequalField>
+    where T: EqualFieldByField + 'a,
+          A: IntoIterator<Item = &'a T>,
+          E: IntoIterator<Item = &'a T>,
+          F: Fn(&T, &T, &str) -> Vec<UnequalField>
+{
+    let actual: Vec<&T> = actual.into_iter().collect();
+    let expected: Vec<&T> = expected.into_iter().collect();
+
+    let mut fields = Vec::new();
+    for i in 0..actual.len().max(expected.len()) {
+        let segment = format!("[{}]", i);
+        match (actual.get(i), expected.get(i)) {
+            (Some(a), Some(e)) => fields.extend(compare(a, e, &segment)
+                                                    .into_iter()
+                                                    .map(|field| field.nested_under(&segment))),
+            (Some(a), None) => fields.push(UnequalField {
+                field_name: segment,
+                actually: Box::new(a.debug_dump()),
+                expected: Box::new(Rendered::absent()),
+                kind: DiffKind::OnlyInActual,
+            }),
+            (None, Some(e)) => fields.push(UnequalField {
+                field_name: segment,
+                actually: Box::new(Rendered::absent()),
+                expected: Box::new(e.debug_dump()),
+                kind: DiffKind::OnlyInExpected,
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+    fields
+}
+
+/// Report the elements between two aligned ones, and start a new gap
+fn report_gap<T: EqualFieldByField>(actual: &[&T],
+                                    expected: &[&T],
+                                    inserted: &mut Vec<usize>,
+                                    removed: &mut Vec<usize>,
+                                    fields: &mut Vec<UnequalField>) {
+    let modified = inserted.len().min(removed.len());
+    for (&i, &j) in inserted.iter().zip(removed.iter()) {
+        let segment = format!("[{}]~[{}]", i, j);
+        fields.extend(actual[i].fields_not_equal(expected[j])
+                          .into_iter()
+                          .map(|field| field.nested_under(&segment)));
+    }
+    for &i in &inserted[modified..] {
+        fields.push(UnequalField {
+            field_name: format!("[{}]", i),
+            actually: Box::new(actual[i].debug_dump()),
+            expected: Box::new(Rendered::absent()),
+            kind: DiffKind::OnlyInActual,
+        });
+    }
+    for &j in &removed[modified..] {
+        fields.push(UnequalField {
+            field_name: format!("~[{}]", j),
+            actually: Box::new(Rendered::absent()),
+            expected: Box::new(expected[j].debug_dump()),
+            kind: DiffKind::OnlyInExpected,
+        });
+    }
+    inserted.clear();
+    removed.clear();
+}
@@ -0,0 +1,80 @@
+//! Comparing a whole table of test cases before failing
+
+use std::fmt::{self, Display};
+
+use {Diff, EqualFieldByField};
+
+/// The result of comparing every case in a table, from `compare_cases`
+#[derive(Debug, Default)]
+pub struct CaseReport {
+    /// The names of the cases that were equal
+    pub passed: Vec<String>,
+    /// The names of the cases that weren't, with their differences
+    pub failed: Vec<(String, Diff)>,
+}
+
+impl CaseReport {
+    /// True if every case was equal
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Every failed case, then a summary
+///
+/// ```text
+///     case `empty_cart`: total: 0 != 1
+///     case `two_items`:
+///         items: 1 != 2
+///         total: 0 != 1
+///
+///     2 of 5 cases failed, 3 passed
+/// ```
+impl Display for CaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, diff) in &self.failed {
+            if diff.leaf_count() == 1 {
+                write!(f, "    case `{}`: {}", name, diff.render(0))?;
+            } else {
+                write!(f, "    case `{}`:\n{}", name, diff.render(8))?;
+            }
+        }
+        if !self.failed.is_empty() {
+            writeln!(f)?;
+        }
+        writeln!(f, "    {} of {} cases failed, {} passed",
+                 self.failed.len(), self.failed.len() + self.passed.len(), self.passed.len())
+    }
+}
+
+/// Compare the actual and expected value of every named case
+///
+/// e.g. `compare_cases(vec![("empty_cart", total(&[]), Cart { total: 0 })])`
+pub fn compare_cases<I, N, T>(cases: I) -> CaseReport
+    where I: IntoIterator<Item = (N, T, T)>,
+          N: Into<String>,
+          T: EqualFieldByField
+{
+    let mut report = CaseReport::default();
+    for (name, actual, expected) in cases {
+        let diff = actual.diff(&expected);
+        if diff.is_empty() {
+            report.passed.push(name.into());
+        } else {
+            report.failed.push((name.into(), diff));
+        }
+    }
+    report
+}
+
+/// Compare every case like `compare_cases`, and panic with the report if any failed
+pub fn assert_cases<I, N, T>(cases: I)
+    where I: IntoIterator<Item = (N, T, T)>,
+          N: Into<String>,
+          T: EqualFieldByField
+{
+    let report = compare_cases(cases);
+    if !report.is_success() {
+        panic!("\n{}", report);
+    }
+}
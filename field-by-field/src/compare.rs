@@ -0,0 +1,96 @@
+//! A builder for one-off comparisons with runtime options
+
+use options::Tolerance;
+use report::labelled_not_equal_message;
+use {CompareOptions, Diff, EqualFieldByField};
+
+/// A comparison of two values, returned by `EqualFieldByField::compare`
+///
+/// e.g. `got.compare(&want).ignoring(&["sensor"]).unordered("tags").assert()`
+#[must_use = "a comparison does nothing until it is asserted or its diff is taken"]
+pub struct Comparison<'a, T: 'a> {
+    actual: &'a T,
+    expected: &'a T,
+    opts: CompareOptions,
+    labels: (String, String),
+}
+
+impl<'a, T: EqualFieldByField> Comparison<'a, T> {
+    pub fn new(actual: &'a T, expected: &'a T) -> Comparison<'a, T> {
+        Comparison {
+            actual,
+            expected,
+            opts: CompareOptions::new(),
+            labels: ("actually".into(), "expected".into()),
+        }
+    }
+
+    /// Don't report differences at these paths, which can be globs
+    pub fn ignoring<I>(mut self, paths: I) -> Comparison<'a, T>
+        where I: IntoIterator,
+              I::Item: AsRef<str>
+    {
+        for path in paths {
+            self.opts = self.opts.ignore(path.as_ref());
+        }
+        self
+    }
+
+    /// Treat floats anywhere in the values as equal if they're within `delta`
+    ///
+    /// This applies to `f32` and `f64` fields, `Option`s of them, and
+    /// `Approximate` newtypes around them. Integers still have to be equal, use
+    /// `CompareOptions::tolerance` with `Tolerance::Absolute` to loosen those.
+    pub fn with_float_tolerance(mut self, delta: f64) -> Comparison<'a, T> {
+        self.opts = self.opts.tolerance("**", Tolerance::FloatAbsolute(delta));
+        self
+    }
+
+    /// Compare the sequence at this path as a multiset
+    pub fn unordered(mut self, path: &str) -> Comparison<'a, T> {
+        self.opts = self.opts.unordered(path);
+        self
+    }
+
+    /// Ignore extra entries in actual's maps and sequences, see `CompareOptions::subset`
+    pub fn subset(mut self) -> Comparison<'a, T> {
+        self.opts = self.opts.subset();
+        self
+    }
+
+    /// Start over from these options, e.g. ones loaded from a config file
+    ///
+    /// Options set on the builder after this are added to them.
+    pub fn with_options(mut self, opts: CompareOptions) -> Comparison<'a, T> {
+        self.opts = opts;
+        self
+    }
+
+    /// Name the two sides something other than `actually` and `expected`
+    pub fn labels<A: Into<String>, E: Into<String>>(mut self, actual: A, expected: E)
+                                                     -> Comparison<'a, T> {
+        self.labels = (actual.into(), expected.into());
+        self
+    }
+
+    /// The differences between the two values, as a tree
+    pub fn diff(&self) -> Diff {
+        Diff::from_fields(self.actual.fields_not_equal_with(self.expected, &self.opts))
+    }
+
+    /// `Ok` if the values are equal, otherwise the differences between them
+    pub fn result(&self) -> Result<(), Diff> {
+        let diff = self.diff();
+        if diff.is_empty() { Ok(()) } else { Err(diff) }
+    }
+
+    /// Panics, like `assert_equal_field_by_field`, if the values differ
+    pub fn assert(&self) {
+        if let Err(diff) = self.result() {
+            panic!("{}", labelled_not_equal_message(&diff,
+                                                    (&self.labels.0, &self.labels.1),
+                                                    &self.actual.debug_dump(),
+                                                    &self.expected.debug_dump()));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Reading comparison configs from TOML or JSON
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(any(feature = "toml", feature = "json"))]
+use path;
+#[cfg(feature = "toml")]
+use toml::de::{DeTable, DeValue};
+
+/// Why a comparison config couldn't be loaded
+#[derive(Debug)]
+pub struct ConfigError {
+    /// The line of the config the problem was found on, starting from 1, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new<S: Into<String>>(line: Option<usize>, message: S) -> ConfigError {
+        ConfigError { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// A rule string for the fields at a path, and the line it was on, if known
+pub type Rule = (String, String, Option<usize>);
+
+/// Parse the rules of a TOML or JSON config, keyed by path
+///
+/// A config that starts with `{` is JSON, anything else is TOML. Tables and
+/// nested objects add to the path, so `[load]` followed by `latency_ms = "rel
+/// 5%"` is the same as `"load.latency_ms" = "rel 5%"`, and every other value
+/// must be a rule string. Rules are returned in the order they're written.
+pub fn parse_rules(text: &str) -> Result<Vec<Rule>, ConfigError> {
+    if text.trim_start().starts_with('{') {
+        parse_json(text)
+    } else {
+        parse_toml(text)
+    }
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml(text: &str) -> Result<Vec<Rule>, ConfigError> {
+    let table = DeTable::parse(text).map_err(|e| {
+        let line = e.span().map(|span| line_at(text, span.start));
+        ConfigError::new(line, e.message().trim_end())
+    })?;
+    let mut rules = Vec::new();
+    collect_toml(text, "", table.get_ref(), &mut rules)?;
+    rules.sort_by_key(|&(_, _, _, start)| start);
+    Ok(rules.into_iter().map(|(path, rule, line, _)| (path, rule, Some(line))).collect())
+}
+
+/// Add the rules in `table` to `rules`, along with where their values start
+#[cfg(feature = "toml")]
+fn collect_toml(text: &str,
+                prefix: &str,
+                table: &DeTable,
+                rules: &mut Vec<(String, String, usize, usize)>)
+-> Result<(), ConfigError> {
+    for (key, value) in table {
+        let path = path::join(prefix, key.get_ref());
+        let start = value.span().start;
+        let line = line_at(text, start);
+        match *value.get_ref() {
+            DeValue::String(ref rule) => rules.push((path, rule.to_string(), line, start)),
+            DeValue::Table(ref table) => collect_toml(text, &path, table, rules)?,
+            _ => return Err(ConfigError::new(Some(line), not_a_rule(&path))),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "toml")]
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml(_: &str) -> Result<Vec<Rule>, ConfigError> {
+    Err(ConfigError::new(None, "TOML configs need the `toml` feature of field-by-field"))
+}
+
+#[cfg(feature = "json")]
+fn parse_json(text: &str) -> Result<Vec<Rule>, ConfigError> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| {
+        let message = e.to_string();
+        let message = message.split(" at line ").next().unwrap_or("");
+        ConfigError::new(Some(e.line()), message)
+    })?;
+    let mut rules = Vec::new();
+    collect_json("", &value, &mut rules)?;
+    Ok(rules)
+}
+
+/// Add the rules in `value` to `rules`
+///
+/// serde_json doesn't keep track of where values are, so these rules don't
+/// have a line, and errors name the path instead.
+#[cfg(feature = "json")]
+fn collect_json(prefix: &str, value: &serde_json::Value, rules: &mut Vec<Rule>)
+-> Result<(), ConfigError> {
+    match *value {
+        serde_json::Value::String(ref rule) =>
+            rules.push((prefix.to_string(), rule.clone(), None)),
+        serde_json::Value::Object(ref object) => {
+            for (key, value) in object {
+                collect_json(&path::join(prefix, key), value, rules)?;
+            }
+        }
+        _ => return Err(ConfigError::new(None, not_a_rule(prefix))),
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "json"))]
+fn parse_json(_: &str) -> Result<Vec<Rule>, ConfigError> {
+    Err(ConfigError::new(None, "JSON configs need the `json` feature of field-by-field"))
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn not_a_rule(path: &str) -> String {
+    format!("expected a rule string or a table for `{}`", path)
+}
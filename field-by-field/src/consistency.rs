@@ -0,0 +1,116 @@
+//! Checks that a type's `PartialEq` agrees with its field by field comparison
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use {path, DiffKind, EqualFieldByField, UnequalField};
+
+/// Panics if `PartialEq` and `fields_not_equal` disagree about `a` and `b`
+///
+/// Checks that `a == b` exactly when `a.fields_not_equal(b)` is empty, and
+/// that swapping `a` and `b` gives the same answers and the same differing
+/// fields, up to which side element indices are on. Feed it lots of
+/// generated values (e.g. with proptest) to catch a hand-written `PartialEq`
+/// that forgot about a newly added field.
+pub fn check_consistency<T>(a: &T, b: &T)
+    where T: EqualFieldByField + PartialEq
+{
+    let problems = consistency_problems(a, b);
+    if !problems.is_empty() {
+        panic_with(problems, a, b);
+    }
+}
+
+/// Like `check_consistency`, but also checks that equal values hash the same
+pub fn check_consistency_with_hash<T>(a: &T, b: &T)
+    where T: EqualFieldByField + PartialEq + Hash
+{
+    let mut problems = consistency_problems(a, b);
+    if a == b && hash_of(a) != hash_of(b) {
+        problems.push("a == b, but they hash differently".to_string());
+    }
+    if !problems.is_empty() {
+        panic_with(problems, a, b);
+    }
+}
+
+fn consistency_problems<T>(a: &T, b: &T) -> Vec<String>
+    where T: EqualFieldByField + PartialEq
+{
+    let mut problems = Vec::new();
+    let forward = a.fields_not_equal(b);
+    let backward = b.fields_not_equal(a);
+
+    let (a_eq_b, b_eq_a) = (a == b, b == a);
+
+    if a_eq_b && !forward.is_empty() {
+        problems.push(format!("a == b, but these fields differ: {}", field_names(&forward)));
+    }
+    if !a_eq_b && forward.is_empty() {
+        problems.push("a != b, but no fields differ".to_string());
+    }
+    if a_eq_b != b_eq_a {
+        problems.push(format!("a == b is {}, but b == a is {}", a_eq_b, b_eq_a));
+    }
+    if field_shapes(&forward, false) != field_shapes(&backward, true) {
+        problems.push(format!("a.fields_not_equal(b) found [{}], but b.fields_not_equal(a) \
+                               found [{}]",
+                              field_names(&forward), field_names(&backward)));
+    }
+    problems
+}
+
+/// The differing fields in a form that doesn't change when the sides are swapped
+///
+/// Element indices are dropped, because paired and aligned sequences say
+/// which side an index is on (`[1]~[0]` becomes `[0]~[1]` when swapped), and
+/// which elements get paired up can depend on the order. What's left is the
+/// path with `[]` for every index, and what kind of difference it is, as seen
+/// from `a`.
+fn field_shapes(fields: &[UnequalField], swapped: bool) -> Vec<(String, DiffKind)> {
+    let mut shapes = fields.iter()
+        .map(|f| {
+            let name = path::segments(&f.field_name)
+                .into_iter()
+                .map(|segment| if is_index(segment) { "[]" } else { segment })
+                .fold(String::new(), |name, segment| path::join(&name, segment));
+            let kind = match f.kind {
+                DiffKind::OnlyInActual if swapped => DiffKind::OnlyInExpected,
+                DiffKind::OnlyInExpected if swapped => DiffKind::OnlyInActual,
+                kind => kind,
+            };
+            (name, kind)
+        })
+        .collect::<Vec<_>>();
+    shapes.sort_by(|a, b| a.0.cmp(&b.0).then((a.1 as u8).cmp(&(b.1 as u8))));
+    shapes
+}
+
+/// True for element indices like `[2]`, `~[2]` and `[2]~[0]`, but not map keys
+fn is_index(segment: &str) -> bool {
+    segment.split('~')
+        .filter(|part| !part.is_empty())
+        .all(|part| {
+            part.starts_with('[') && part.ends_with(']') && part.len() > 2 &&
+            part[1..part.len() - 1].bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+fn field_names(fields: &[UnequalField]) -> String {
+    fields.iter().map(|f| f.field_name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn panic_with<T: EqualFieldByField>(problems: Vec<String>, a: &T, b: &T) -> ! {
+    let mut errmsg = String::from("\n    PartialEq is inconsistent with fields_not_equal:\n");
+    for problem in problems {
+        errmsg.push_str(&format!("        {}\n", problem));
+    }
+    errmsg.push_str(&format!("    a: {:?}\n    b: {:?}\n", a.debug_dump(), b.debug_dump()));
+    panic!("{}", errmsg);
+}
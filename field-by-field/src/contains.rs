@@ -0,0 +1,45 @@
+//! Looking for a value in a collection, and explaining why it isn't there
+
+use {Diff, EqualFieldByField};
+
+/// The element of `haystack` with the fewest fields differing from `needle`
+///
+/// Returns its index and its differences from the needle, which are empty if
+/// it's equal. The first of several equally close elements wins, and an
+/// empty haystack has no closest element.
+pub fn closest_match<T: EqualFieldByField>(haystack: &[T], needle: &T) -> Option<(usize, Diff)> {
+    let mut closest: Option<(usize, Diff)> = None;
+    for (i, candidate) in haystack.iter().enumerate() {
+        let diff = candidate.diff(needle);
+        if diff.is_empty() {
+            return Some((i, diff));
+        }
+        let closer = closest.as_ref().is_none_or(|(_, best)| diff.leaf_count() < best.leaf_count());
+        if closer {
+            closest = Some((i, diff));
+        }
+    }
+    closest
+}
+
+/// Panics if no element of `haystack` is equal to `needle` field by field
+///
+/// The failure shows the closest element, and how it differs:
+///
+/// ```text
+///     Item not found among 500 elements, the closest is [17]:
+///         timestamp: 5 != 6
+///     closest: Event { id: 3, timestamp: 5 }
+///      needle: Event { id: 3, timestamp: 6 }
+/// ```
+pub fn assert_contains_field_by_field<T: EqualFieldByField>(haystack: &[T], needle: &T) {
+    match closest_match(haystack, needle) {
+        Some((_, ref diff)) if diff.is_empty() => {}
+        Some((i, diff)) => panic!("\n    Item not found among {} elements, the closest is [{}]:\n\
+                                   {}    closest: {:?}\n     needle: {:?}\n",
+                                  haystack.len(), i, diff.render(8), haystack[i].debug_dump(),
+                                  needle.debug_dump()),
+        None => panic!("\n    Item not found in an empty collection\n     needle: {:?}\n",
+                       needle.debug_dump()),
+    }
+}
@@ -0,0 +1,298 @@
+//! A tree of differences, grouped by their path
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+
+use path;
+use UnequalField;
+
+/// How a value differs between actual and expected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffKind {
+    /// The value is on both sides, but isn't equal
+    Changed,
+    /// The value is only in actual, e.g. an unexpected element
+    OnlyInActual,
+    /// The value is only in expected, e.g. a missing element
+    OnlyInExpected,
+    /// The value doesn't satisfy a matcher, which is the expected side
+    Unmatched,
+}
+
+/// The differences between two values, as a tree of path segments
+///
+/// `customer.address.zip` and `customer.name` both live under a single
+/// `customer` node. Build one with `EqualFieldByField::diff` or
+/// `Diff::from_fields`, and turn it back into a flat list with `into_fields`.
+#[derive(Debug)]
+pub enum Diff {
+    /// A path segment like `customer` or `[2]`, and the differences below it
+    ///
+    /// The root of a tree is a node with an empty segment.
+    Node {
+        segment: String,
+        children: Vec<Diff>,
+    },
+    /// A single difference, at the path of the nodes above it
+    Leaf {
+        actual: Box<dyn Debug>,
+        expected: Box<dyn Debug>,
+        kind: DiffKind,
+    },
+}
+
+/// A borrowed leaf of a `Diff`, along with its full path
+pub struct LeafRef<'a> {
+    pub path: String,
+    pub actual: &'a dyn Debug,
+    pub expected: &'a dyn Debug,
+    pub kind: DiffKind,
+}
+
+impl Diff {
+    /// An empty tree, for two values with no differences
+    pub fn empty() -> Diff {
+        Diff::Node { segment: String::new(), children: Vec::new() }
+    }
+
+    /// Group a flat list of differences by path
+    pub fn from_fields(fields: Vec<UnequalField>) -> Diff {
+        let mut root = NodeBuilder::default();
+        for field in fields {
+            let leaf = Diff::Leaf {
+                actual: field.actually,
+                expected: field.expected,
+                kind: field.kind,
+            };
+            root.insert(&path::segments(&field.field_name), leaf);
+        }
+        root.build(String::new())
+    }
+
+    /// Flatten the tree back into a list of differences, in the same order
+    pub fn into_fields(self) -> Vec<UnequalField> {
+        let mut fields = Vec::new();
+        self.into_fields_under("", &mut fields);
+        fields
+    }
+
+    /// True if there are no differences at all
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count() == 0
+    }
+
+    /// The number of differences in the tree
+    pub fn leaf_count(&self) -> usize {
+        match *self {
+            Diff::Node { ref children, .. } => children.iter().map(Diff::leaf_count).sum(),
+            Diff::Leaf { .. } => 1,
+        }
+    }
+
+    /// Every difference in the tree, with its full path, in order
+    pub fn leaves(&self) -> Vec<LeafRef<'_>> {
+        let mut leaves = Vec::new();
+        self.walk(&mut |path, diff| {
+            if let Diff::Leaf { ref actual, ref expected, kind } = *diff {
+                leaves.push(LeafRef {
+                    path: path.to_string(),
+                    actual: &**actual,
+                    expected: &**expected,
+                    kind,
+                });
+            }
+        });
+        leaves
+    }
+
+    /// Call `f` with every node and leaf in the tree, parents first
+    ///
+    /// `f` gets the full path of each node, or of the node containing a leaf.
+    pub fn walk<'a, F: FnMut(&str, &'a Diff)>(&'a self, f: &mut F) {
+        self.walk_under("", f)
+    }
+
+    /// Only keep the differences for which `keep(path, kind)` is true
+    ///
+    /// Nodes that end up without any differences are removed.
+    pub fn retain<F: FnMut(&str, DiffKind) -> bool>(&mut self, mut keep: F) {
+        self.retain_under("", &mut keep);
+    }
+
+    /// Remove every subtree whose path `prune(path)` is true for
+    pub fn prune<F: FnMut(&str) -> bool>(&mut self, mut prune: F) {
+        self.prune_under("", &mut prune);
+    }
+
+    /// Render the differences as an indented tree, one difference per line
+    ///
+    /// Siblings are grouped under their common parent, and chains of nodes
+    /// leading to a single difference are collapsed into one line:
+    ///
+    /// ```text
+    /// customer
+    ///   name: "bob" != "alice"
+    ///   address.zip: "02139" != "02138"
+    /// lines[2].qty: 1 != 2
+    /// ```
+    ///
+    /// Every line is indented by at least `indent` spaces.
+    pub fn render(&self, indent: usize) -> String {
+        let mut out = String::new();
+        match *self {
+            Diff::Node { ref segment, ref children } if segment.is_empty() =>
+                render_children(children, indent, &mut out),
+            Diff::Node { ref segment, ref children } =>
+                render_node(segment, children, indent, &mut out),
+            Diff::Leaf { .. } => render_leaf("", self, indent, &mut out),
+        }
+        out
+    }
+
+    fn into_fields_under(self, parent: &str, fields: &mut Vec<UnequalField>) {
+        match self {
+            Diff::Node { segment, children } => {
+                let path = path::join(parent, &segment);
+                for child in children {
+                    child.into_fields_under(&path, fields);
+                }
+            }
+            Diff::Leaf { actual, expected, kind } => fields.push(UnequalField {
+                field_name: parent.to_string(),
+                actually: actual,
+                expected,
+                kind,
+            }),
+        }
+    }
+
+    fn walk_under<'a, F: FnMut(&str, &'a Diff)>(&'a self, parent: &str, f: &mut F) {
+        match *self {
+            Diff::Node { ref segment, ref children } => {
+                let path = path::join(parent, segment);
+                f(&path, self);
+                for child in children {
+                    child.walk_under(&path, f);
+                }
+            }
+            Diff::Leaf { .. } => f(parent, self),
+        }
+    }
+
+    fn retain_under<F: FnMut(&str, DiffKind) -> bool>(&mut self, parent: &str, keep: &mut F) {
+        if let Diff::Node { ref segment, ref mut children } = *self {
+            let path = path::join(parent, segment);
+            children.retain(|child| match *child {
+                Diff::Leaf { kind, .. } => keep(&path, kind),
+                Diff::Node { .. } => true,
+            });
+            for child in children.iter_mut() {
+                child.retain_under(&path, keep);
+            }
+            children.retain(|child| !child.is_empty());
+        }
+    }
+
+    fn prune_under<F: FnMut(&str) -> bool>(&mut self, parent: &str, prune: &mut F) {
+        if let Diff::Node { ref segment, ref mut children } = *self {
+            let path = path::join(parent, segment);
+            children.retain(|child| match *child {
+                Diff::Node { ref segment, .. } => !prune(&path::join(&path, segment)),
+                Diff::Leaf { .. } => true,
+            });
+            for child in children.iter_mut() {
+                child.prune_under(&path, prune);
+            }
+            children.retain(|child| !child.is_empty());
+        }
+    }
+}
+
+
+/// A `Diff::Node` under construction, with its child nodes indexed by segment
+///
+/// Without the index, grouping tens of thousands of differences under one
+/// node would take quadratic time.
+#[derive(Default)]
+struct NodeBuilder {
+    children: Vec<ChildBuilder>,
+    nodes: HashMap<String, usize>,
+}
+
+enum ChildBuilder {
+    Node(String, NodeBuilder),
+    Leaf(Diff),
+}
+
+impl NodeBuilder {
+    fn insert(&mut self, segments: &[&str], leaf: Diff) {
+        let (first, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                self.children.push(ChildBuilder::Leaf(leaf));
+                return;
+            }
+        };
+        let children = &mut self.children;
+        let index = *self.nodes.entry(first.to_string()).or_insert_with(|| {
+            children.push(ChildBuilder::Node(first.to_string(), NodeBuilder::default()));
+            children.len() - 1
+        });
+        match self.children[index] {
+            ChildBuilder::Node(_, ref mut node) => node.insert(rest, leaf),
+            ChildBuilder::Leaf(_) => unreachable!("Only nodes are indexed"),
+        }
+    }
+
+    fn build(self, segment: String) -> Diff {
+        let children = self.children
+            .into_iter()
+            .map(|child| match child {
+                ChildBuilder::Node(segment, node) => node.build(segment),
+                ChildBuilder::Leaf(leaf) => leaf,
+            })
+            .collect();
+        Diff::Node { segment, children }
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(0))
+    }
+}
+
+fn render_children(children: &[Diff], indent: usize, out: &mut String) {
+    for child in children {
+        match *child {
+            Diff::Node { ref segment, ref children } => render_node(segment, children, indent, out),
+            Diff::Leaf { .. } => render_leaf("", child, indent, out),
+        }
+    }
+}
+
+/// Render a node, collapsing it into its child if it only has one
+fn render_node(label: &str, children: &[Diff], indent: usize, out: &mut String) {
+    if children.len() == 1 {
+        match children[0] {
+            Diff::Node { ref segment, ref children } =>
+                return render_node(&path::join(label, segment), children, indent, out),
+            ref leaf => return render_leaf(label, leaf, indent, out),
+        }
+    }
+    out.push_str(&format!("{:indent$}{}\n", "", label, indent = indent));
+    render_children(children, indent + 2, out);
+}
+
+fn render_leaf(label: &str, leaf: &Diff, indent: usize, out: &mut String) {
+    if let Diff::Leaf { ref actual, ref expected, kind } = *leaf {
+        let prefix = if label.is_empty() { String::new() } else { format!("{}: ", label) };
+        let line = match kind {
+            DiffKind::Changed => format!("{:?} != {:?}", actual, expected),
+            DiffKind::OnlyInActual => format!("unexpected {:?}", actual),
+            DiffKind::OnlyInExpected => format!("missing {:?}", expected),
+            DiffKind::Unmatched => format!("{:?} does not {:?}", actual, expected),
+        };
+        out.push_str(&format!("{:indent$}{}{}\n", "", prefix, line, indent = indent));
+    }
+}
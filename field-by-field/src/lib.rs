@@ -1,10 +1,71 @@
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "toml")]
+extern crate toml;
+
 use std::fmt::Debug;
 
+mod align;
+mod cases;
+mod compare;
+mod config;
+mod contains;
+mod consistency;
+mod diff;
+mod matchers;
+mod options;
+mod pairing;
+pub mod path;
+#[macro_use]
+mod pattern;
+mod records;
+mod render;
+mod report;
+mod seq;
+mod soft;
+
+pub use align::{aligned_diff, indexed_diff};
+pub use cases::{assert_cases, compare_cases, CaseReport};
+pub use compare::Comparison;
+pub use config::ConfigError;
+pub use contains::{assert_contains_field_by_field, closest_match};
+pub use consistency::{check_consistency, check_consistency_with_hash};
+pub use diff::{Diff, DiffKind, LeafRef};
+pub use matchers::{any, between, equals, is_none, is_some, satisfies, Expect};
+#[cfg(feature = "regex")]
+pub use matchers::matches_regex;
+pub use options::{Approximate, CompareOptions, Tolerance};
+#[doc(hidden)]
+pub use options::{MaybeApproximate, ViaApproximate, ViaExact};
+pub use pairing::{min_cost_assignment, paired_unordered_diff};
+pub use records::{assert_records_eq_by_key, records_diff_by_key};
+pub use render::{debug_with, DebugWith, Redacted, Rendered};
+pub use report::{changed_only_message, labelled_not_equal_message, mismatch_message,
+                 not_equal_message};
+#[doc(hidden)]
+pub use render::{MaybeDebug, ViaDebug, ViaPlaceholder};
+pub use seq::{unordered_diff, UnorderedDiff};
+pub use soft::{SoftAsserter, SoftFailure};
+
 #[derive(Debug)]
 pub struct UnequalField {
     pub field_name: String,
-    pub actually: Box<Debug>,
-    pub expected: Box<Debug>,
+    pub actually: Box<dyn Debug>,
+    pub expected: Box<dyn Debug>,
+    pub kind: DiffKind,
+}
+
+impl UnequalField {
+    /// Move this field underneath `parent` in the path
+    ///
+    /// `zip` under `address` becomes `address.zip`, `[2]` under `lines`
+    /// becomes `lines[2]`, and an empty name or parent is left out.
+    pub fn nested_under(mut self, parent: &str) -> UnequalField {
+        self.field_name = path::join(parent, &self.field_name);
+        self
+    }
 }
 
 
@@ -15,6 +76,71 @@ pub trait EqualFieldByField {
     /// Get a list of the fields that are not equal
     fn fields_not_equal(&self, other: &Self) -> Vec<UnequalField>;
 
+    /// Get the fields that are not equal, following some runtime options
+    ///
+    /// e.g. `a.fields_not_equal_with(&b, &CompareOptions::new().ignore("**.id"))`
+    fn fields_not_equal_with(&self, other: &Self, opts: &CompareOptions) -> Vec<UnequalField> {
+        self.fields_not_equal(other)
+            .into_iter()
+            .filter(|field| opts.reports(field))
+            .collect()
+    }
+
+    /// Start a comparison with `expected` that can be configured before it's run
+    ///
+    /// e.g. `actual.compare(&expected).ignoring(&["id"]).labels("got", "want").assert()`
+    fn compare<'a>(&'a self, expected: &'a Self) -> Comparison<'a, Self>
+        where Self: Sized
+    {
+        Comparison::new(self, expected)
+    }
+
+    /// Panics unless `self` contains everything in `expected`
+    ///
+    /// Fields are compared as with `CompareOptions::subset`, so maps and
+    /// sequences in `self` can have extra entries.
+    fn assert_contains_fields(&self, expected: &Self)
+        where Self: Sized
+    {
+        self.compare(expected).subset().assert();
+    }
+
+    /// Panics unless exactly the `changed` fields differ between `self` and `after`
+    ///
+    /// e.g. `before.assert_changed_only(&after, &["status", "updated_at"])`
+    /// fails if anything other than `status` and `updated_at` changed, or if
+    /// either of them didn't. Entries are path globs like in `CompareOptions`,
+    /// so `customer` allows any change underneath it.
+    fn assert_changed_only(&self, after: &Self, changed: &[&str]) {
+        let mut unexpected = self.fields_not_equal(after);
+        let changed_at = |pattern: &str, field: &UnequalField| {
+            path::matches(pattern, &field.field_name)
+        };
+        let unchanged: Vec<&str> = changed.iter()
+            .filter(|pattern| !unexpected.iter().any(|field| changed_at(pattern, field)))
+            .cloned()
+            .collect();
+        unexpected.retain(|field| !changed.iter().any(|pattern| changed_at(pattern, field)));
+        if !unexpected.is_empty() || !unchanged.is_empty() {
+            panic!("{}", changed_only_message(&Diff::from_fields(unexpected), &unchanged));
+        }
+    }
+
+    /// Get the fields that are not equal as a tree, grouped by their path
+    fn diff(&self, other: &Self) -> Diff {
+        Diff::from_fields(self.fields_not_equal(other))
+    }
+
+    /// The whole value as it's shown in failure messages
+    ///
+    /// Derived impls use `Debug`, with `#[field_by_field(redact)]` fields
+    /// hidden. The default is a placeholder naming the type.
+    fn debug_dump(&self) -> Rendered {
+        #[allow(unused_imports)]
+        use render::{ViaDebug, ViaPlaceholder};
+        (&MaybeDebug(self)).rendered()
+    }
+
     /// Panics if all the fields on self are not equal to the fields on "other"
     fn assert_equal_field_by_field(&self, other: &Self);
 }
@@ -0,0 +1,94 @@
+//! Matchers, for expecting something about a value rather than an exact value
+
+use std::fmt::Debug;
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use render::Rendered;
+
+/// What a field of an expectation requires of the actual value
+///
+/// Build one with a matcher function like `between(1, 10)`, or from a plain
+/// value with `.into()`, which expects exactly that value.
+pub struct Expect<T: ?Sized> {
+    matches: Box<dyn Fn(&T) -> bool>,
+    /// Finishes the sentence "the value does not ...", e.g. `match /^ord_/`
+    description: String,
+}
+
+impl<T: ?Sized> Expect<T> {
+    pub fn new<D, F>(description: D, matches: F) -> Expect<T>
+        where D: Into<String>,
+              F: Fn(&T) -> bool + 'static
+    {
+        Expect { matches: Box::new(matches), description: description.into() }
+    }
+
+    /// True if `actual` satisfies this expectation
+    pub fn matches(&self, actual: &T) -> bool {
+        (self.matches)(actual)
+    }
+
+    /// What this expects, for the expected side of an `UnequalField`
+    pub fn description(&self) -> Rendered {
+        Rendered::display(&self.description)
+    }
+}
+
+impl<T: PartialEq + Debug + 'static> From<T> for Expect<T> {
+    fn from(value: T) -> Expect<T> {
+        equals(value)
+    }
+}
+
+/// Matches anything
+pub fn any<T: ?Sized>() -> Expect<T> {
+    Expect::new("match anything", |_| true)
+}
+
+/// Matches values equal to `expected`
+pub fn equals<T: PartialEq + Debug + 'static>(expected: T) -> Expect<T> {
+    Expect::new(format!("equal {:?}", expected), move |actual| *actual == expected)
+}
+
+/// Matches values from `low` to `high`, inclusive
+pub fn between<T: PartialOrd + Debug + 'static>(low: T, high: T) -> Expect<T> {
+    Expect::new(format!("lie between {:?} and {:?}", low, high),
+                move |actual| low <= *actual && *actual <= high)
+}
+
+/// Matches `Some` of anything
+pub fn is_some<T>() -> Expect<Option<T>> {
+    Expect::new("have a value", |actual: &Option<T>| actual.is_some())
+}
+
+/// Matches `None`
+pub fn is_none<T>() -> Expect<Option<T>> {
+    Expect::new("equal None", |actual: &Option<T>| actual.is_none())
+}
+
+/// Matches values for which `predicate` returns true
+///
+/// The description finishes the sentence "the value does not ...", e.g.
+/// `satisfies("have an even length", |s: &String| s.len() % 2 == 0)`.
+pub fn satisfies<T, D, F>(description: D, predicate: F) -> Expect<T>
+    where T: ?Sized,
+          D: Into<String>,
+          F: Fn(&T) -> bool + 'static
+{
+    Expect::new(description, predicate)
+}
+
+/// Matches strings that contain a match for `pattern`
+///
+/// `pattern` uses the syntax of the `regex` crate, so anchor it with `^` and
+/// `$` to match whole strings. Only available with the `regex` feature.
+///
+/// Panics if `pattern` isn't a valid regex.
+#[cfg(feature = "regex")]
+pub fn matches_regex<T: AsRef<str> + ?Sized>(pattern: &str) -> Expect<T> {
+    let regex = Regex::new(pattern)
+        .unwrap_or_else(|e| panic!("Invalid regex /{}/: {}", pattern, e));
+    Expect::new(format!("match /{}/", pattern), move |actual: &T| regex.is_match(actual.as_ref()))
+}
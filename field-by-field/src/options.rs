@@ -0,0 +1,298 @@
+//! Options that change how values are compared at runtime
+
+use std::fs;
+use std::path::Path;
+
+use config::{self, ConfigError};
+use path;
+use UnequalField;
+
+/// Runtime options for `EqualFieldByField::fields_not_equal_with`
+///
+/// Unlike `#[field_by_field(...)]` attributes, these can be different for
+/// every comparison of the same type:
+///
+/// ```
+/// use field_by_field::CompareOptions;
+///
+/// let opts = CompareOptions::new()
+///     .ignore("**.updated_at")
+///     .ignore("items[*].id");
+/// assert!(opts.is_ignored("order.updated_at"));
+/// assert!(opts.is_ignored("items[3].id"));
+/// assert!(!opts.is_ignored("items[3].qty"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    ignored: Vec<String>,
+    tolerances: Vec<(String, Tolerance)>,
+    unordered: Vec<String>,
+    subset: bool,
+    /// Where the values being compared are, for options passed to nested fields
+    prefix: String,
+}
+
+/// How far apart two numbers can be and still count as equal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// `|actual - expected| <= 0.01`, written `abs 0.01`
+    Absolute(f64),
+    /// `|actual - expected| <= 0.05 * |expected|`, written `rel 5%` or `rel 0.05`
+    Relative(f64),
+    /// Like `Absolute`, but only for `f32` and `f64`: integers still have to be equal
+    FloatAbsolute(f64),
+}
+
+impl Tolerance {
+    /// True if `actual` is close enough to `expected`
+    pub fn allows(&self, actual: f64, expected: f64) -> bool {
+        let allowed = match *self {
+            Tolerance::Absolute(delta) | Tolerance::FloatAbsolute(delta) => delta,
+            Tolerance::Relative(fraction) => fraction * expected.abs(),
+        };
+        (actual - expected).abs() <= allowed
+    }
+}
+
+/// Values that a `Tolerance` can apply to
+///
+/// Implemented for the primitive numbers, and for `Option`s of anything that
+/// implements it, where `None` is only close to `None`. Implement it for
+/// newtypes like `struct Price(f64)` so that tolerances reach their fields.
+pub trait Approximate {
+    /// True if `self` is within `tolerance` of `expected`
+    fn is_close_to(&self, expected: &Self, tolerance: Tolerance) -> bool;
+}
+
+macro_rules! approximate_float {
+    ($($ty:ty),*) => {
+        $(
+            impl Approximate for $ty {
+                fn is_close_to(&self, expected: &$ty, tolerance: Tolerance) -> bool {
+                    tolerance.allows(*self as f64, *expected as f64)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! approximate_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl Approximate for $ty {
+                fn is_close_to(&self, expected: &$ty, tolerance: Tolerance) -> bool {
+                    match tolerance {
+                        Tolerance::FloatAbsolute(_) => self == expected,
+                        _ => tolerance.allows(*self as f64, *expected as f64),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+approximate_float!(f32, f64);
+approximate_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<T: Approximate> Approximate for Option<T> {
+    fn is_close_to(&self, expected: &Option<T>, tolerance: Tolerance) -> bool {
+        match (self, expected) {
+            (Some(actual), Some(expected)) => actual.is_close_to(expected, tolerance),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T: Approximate + ?Sized> Approximate for &'a T {
+    fn is_close_to(&self, expected: &&'a T, tolerance: Tolerance) -> bool {
+        (**self).is_close_to(*expected, tolerance)
+    }
+}
+
+/// Compares with a tolerance if the values are `Approximate`, or with `!=` if not
+///
+/// Like `MaybeDebug`, this relies on autoref-based specialization: it must be
+/// called as `(&MaybeApproximate(&a, &b)).differs_within(tolerance)` with
+/// both `ViaApproximate` and `ViaExact` in scope.
+#[doc(hidden)]
+pub struct MaybeApproximate<'a, T: ?Sized + 'a>(pub &'a T, pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaApproximate {
+    fn differs_within(&self, tolerance: Tolerance) -> bool;
+}
+
+impl<'a, T: Approximate + ?Sized> ViaApproximate for MaybeApproximate<'a, T> {
+    fn differs_within(&self, tolerance: Tolerance) -> bool {
+        !self.0.is_close_to(self.1, tolerance)
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaExact {
+    fn differs_within(&self, tolerance: Tolerance) -> bool;
+}
+
+impl<'a, 'b, T: PartialEq + ?Sized> ViaExact for &'b MaybeApproximate<'a, T> {
+    fn differs_within(&self, _: Tolerance) -> bool {
+        self.0 != self.1
+    }
+}
+
+impl CompareOptions {
+    pub fn new() -> CompareOptions {
+        CompareOptions::default()
+    }
+
+    /// Load the options from a file of rules
+    ///
+    /// See `from_config` for the format.
+    pub fn from_config_file<P: AsRef<Path>>(file: P) -> Result<CompareOptions, ConfigError> {
+        let file = file.as_ref();
+        let text = fs::read_to_string(file).map_err(|e| ConfigError {
+            line: None,
+            message: format!("couldn't read {}: {}", file.display(), e),
+        })?;
+        CompareOptions::from_config(&text)
+    }
+
+    /// Parse options from a TOML document, or a JSON object
+    ///
+    /// Each key is a path, which can be a glob as for `ignore`, and each value
+    /// is a rule: `ignore`, `abs <delta>` or `rel <percent>%`. Tables and
+    /// nested objects add to the path. TOML needs the `toml` feature, and JSON
+    /// (anything starting with `{`) needs the `json` feature.
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "toml", feature = "json"))] {
+    /// use field_by_field::CompareOptions;
+    ///
+    /// let opts = CompareOptions::from_config(r#"
+    ///     revenue = "abs 0.01"
+    ///     "*.latency_ms" = "rel 5%"
+    ///     debug_info = "ignore"
+    /// "#).unwrap();
+    /// assert!(opts.is_ignored("debug_info.trace"));
+    ///
+    /// let json = CompareOptions::from_config(r#"{"revenue": "abs 0.01"}"#).unwrap();
+    /// assert!(!json.is_ignored("revenue"));
+    /// # }
+    /// ```
+    pub fn from_config(text: &str) -> Result<CompareOptions, ConfigError> {
+        let mut opts = CompareOptions::new();
+        for (pattern, rule, line) in config::parse_rules(text)? {
+            let words = rule.split_whitespace().collect::<Vec<_>>();
+            opts = match words[..] {
+                ["ignore"] => opts.ignore(pattern),
+                ["abs", delta] => {
+                    let delta = parse_number(delta, &pattern, line)?;
+                    opts.tolerance(pattern, Tolerance::Absolute(delta))
+                }
+                ["rel", percent] if percent.ends_with('%') => {
+                    let percent = parse_number(&percent[..percent.len() - 1], &pattern, line)?;
+                    opts.tolerance(pattern, Tolerance::Relative(percent / 100.0))
+                }
+                ["rel", fraction] => {
+                    let fraction = parse_number(fraction, &pattern, line)?;
+                    opts.tolerance(pattern, Tolerance::Relative(fraction))
+                }
+                _ => return Err(ConfigError {
+                    line,
+                    message: format!("unknown rule {:?} for `{}`, expected `ignore`, \
+                                      `abs <delta>` or `rel <percent>%`", rule, pattern),
+                }),
+            };
+        }
+        Ok(opts)
+    }
+
+    /// Don't report differences at paths matching this glob, or below them
+    ///
+    /// See `path::matches` for the pattern syntax.
+    pub fn ignore<S: Into<String>>(mut self, pattern: S) -> CompareOptions {
+        self.ignored.push(pattern.into());
+        self
+    }
+
+    /// Treat numbers at paths matching this glob as equal if they are close enough
+    ///
+    /// If several patterns match the same path, the last one added wins.
+    pub fn tolerance<S: Into<String>>(mut self, pattern: S, tolerance: Tolerance)
+                                      -> CompareOptions {
+        self.tolerances.push((pattern.into(), tolerance));
+        self
+    }
+
+    /// Compare sequence fields at paths matching this glob as multisets
+    ///
+    /// This only affects fields of derived structs that are spelled like
+    /// `Vec<T>`, `VecDeque<T>`, `[T; N]` or `&[T]`, as with the `unordered`
+    /// attribute.
+    pub fn unordered<S: Into<String>>(mut self, pattern: S) -> CompareOptions {
+        self.unordered.push(pattern.into());
+        self
+    }
+
+    /// Only check that actual contains everything in expected
+    ///
+    /// Map fields of derived structs (`HashMap` and `BTreeMap`) only need the
+    /// keys that are in expected, and sequence or set fields only need the
+    /// elements that are in expected. Anything extra in actual is ignored.
+    pub fn subset(mut self) -> CompareOptions {
+        self.subset = true;
+        self
+    }
+
+    /// True if extra entries in actual's maps and sequences are ignored
+    pub fn is_subset(&self) -> bool {
+        self.subset
+    }
+
+    /// These options as seen from inside of the field at `field_name`
+    ///
+    /// Used by derived `fields_not_equal_with` impls for `nested` fields, so
+    /// that paths in the options are still matched from the outermost value.
+    pub fn nested(&self, field_name: &str) -> CompareOptions {
+        CompareOptions {
+            prefix: path::join(&self.prefix, field_name),
+            ..self.clone()
+        }
+    }
+
+    /// True if differences at `path` should not be reported
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let path = path::join(&self.prefix, path);
+        self.ignored.iter().any(|pattern| path::matches(pattern, &path))
+    }
+
+    /// True if the sequence at `path` should be compared as a multiset
+    pub fn is_unordered(&self, path: &str) -> bool {
+        let path = path::join(&self.prefix, path);
+        self.unordered.iter().any(|pattern| path::matches(pattern, &path))
+    }
+
+    /// The tolerance for numbers at `path`, if there is one
+    pub fn tolerance_for(&self, path: &str) -> Option<Tolerance> {
+        let path = path::join(&self.prefix, path);
+        self.tolerances.iter()
+            .rev()
+            .find(|&(pattern, _)| path::matches(pattern, &path))
+            .map(|&(_, tolerance)| tolerance)
+    }
+
+    /// True if this difference should be reported with these options
+    ///
+    /// Tolerances aren't checked here: derived `fields_not_equal_with` impls
+    /// apply them while comparing fields whose type is `Approximate`.
+    pub fn reports(&self, field: &UnequalField) -> bool {
+        !self.is_ignored(&field.field_name)
+    }
+}
+
+fn parse_number(text: &str, pattern: &str, line: Option<usize>) -> Result<f64, ConfigError> {
+    text.parse().map_err(|_| ConfigError {
+        line,
+        message: format!("expected a number for `{}`, not `{}`", pattern, text),
+    })
+}
@@ -0,0 +1,145 @@
+//! Pairing up the elements of unordered sequences of structs
+
+use {DiffKind, EqualFieldByField, Rendered, UnequalField};
+
+/// Compare two sequences as multisets, pairing up elements that almost match
+///
+/// Elements are paired so that the total number of differing fields is as
+/// small as possible, and each pair's differing fields are reported under
+/// both indexes, like `[2]~[0].qty` for `actual[2]` paired with `expected[0]`.
+/// If the sequences have different lengths, the leftover elements are
+/// reported as unexpected, under their index in actual (`[2]`), or missing,
+/// under their index in expected (`~[0]`).
+pub fn paired_unordered_diff<'a, T, A, E>(actual: A, expected: E) -> Vec<UnequalField>
+    where T: EqualFieldByField + 'a,
+          A: IntoIterator<Item = &'a T>,
+          E: IntoIterator<Item = &'a T>
+{
+    let actual: Vec<&T> = actual.into_iter().collect();
+    let expected: Vec<&T> = expected.into_iter().collect();
+
+    // Equal elements are always in some optimal pairing, so pair them up
+    // first and leave only the rest for the (cubic) assignment
+    let mut expected_for: Vec<Option<usize>> = vec![None; actual.len()];
+    let mut expected_used = vec![false; expected.len()];
+    for (i, a) in actual.iter().enumerate() {
+        let equal = (0..expected.len())
+            .find(|&j| !expected_used[j] && a.fields_not_equal(expected[j]).is_empty());
+        if let Some(j) = equal {
+            expected_for[i] = Some(j);
+            expected_used[j] = true;
+        }
+    }
+
+    let rows: Vec<usize> = (0..actual.len()).filter(|&i| expected_for[i].is_none()).collect();
+    let cols: Vec<usize> = (0..expected.len()).filter(|&j| !expected_used[j]).collect();
+    let costs: Vec<Vec<usize>> = rows.iter()
+        .map(|&i| cols.iter().map(|&j| actual[i].fields_not_equal(expected[j]).len()).collect())
+        .collect();
+    for (row, col) in min_cost_assignment(&costs).into_iter().enumerate() {
+        if let Some(col) = col {
+            expected_for[rows[row]] = Some(cols[col]);
+            expected_used[cols[col]] = true;
+        }
+    }
+
+    let mut fields = Vec::new();
+    for (i, a) in actual.iter().enumerate() {
+        match expected_for[i] {
+            Some(j) => {
+                let segment = format!("[{}]~[{}]", i, j);
+                fields.extend(a.fields_not_equal(expected[j])
+                                  .into_iter()
+                                  .map(|field| field.nested_under(&segment)));
+            }
+            None => fields.push(UnequalField {
+                field_name: format!("[{}]", i),
+                actually: Box::new(a.debug_dump()),
+                expected: Box::new(Rendered::absent()),
+                kind: DiffKind::OnlyInActual,
+            }),
+        }
+    }
+    for (j, e) in expected.iter().enumerate().filter(|&(j, _)| !expected_used[j]) {
+        fields.push(UnequalField {
+            field_name: format!("~[{}]", j),
+            actually: Box::new(Rendered::absent()),
+            expected: Box::new(e.debug_dump()),
+            kind: DiffKind::OnlyInExpected,
+        });
+    }
+    fields
+}
+
+/// Assign each row to a different column, minimizing the total cost
+///
+/// This is the Hungarian algorithm, and takes `O(n³)` time for `n` rows or
+/// columns, whichever is more. The result is the column for each row, or
+/// `None` for rows left over when there are fewer columns than rows.
+pub fn min_cost_assignment(costs: &[Vec<usize>]) -> Vec<Option<usize>> {
+    let rows = costs.len();
+    let cols = costs.first().map_or(0, |row| row.len());
+    let n = rows.max(cols);
+    // Pad to a square matrix, where the padding costs nothing
+    let cost = |i: usize, j: usize| -> i64 {
+        if i < rows && j < cols { costs[i][j] as i64 } else { 0 }
+    };
+
+    // Potentials for rows and columns, and the row matched to each column,
+    // all 1-indexed with 0 as a sentinel
+    let mut u = vec![0i64; n + 1];
+    let mut v = vec![0i64; n + 1];
+    let mut row_for = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+    for i in 1..n + 1 {
+        row_for[0] = i;
+        let mut j0 = 0;
+        let mut min_to = vec![i64::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_for[j0];
+            let mut delta = i64::MAX;
+            let mut j1 = 0;
+            for j in 1..n + 1 {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                if reduced < min_to[j] {
+                    min_to[j] = reduced;
+                    way[j] = j0;
+                }
+                if min_to[j] < delta {
+                    delta = min_to[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..n + 1 {
+                if used[j] {
+                    u[row_for[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_to[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_for[j0] == 0 {
+                break;
+            }
+        }
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_for[j0] = row_for[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for (j, &i) in row_for.iter().enumerate().skip(1) {
+        if i >= 1 && i <= rows && j <= cols {
+            assignment[i - 1] = Some(j - 1);
+        }
+    }
+    assignment
+}
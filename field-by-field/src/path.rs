@@ -0,0 +1,116 @@
+//! Field paths like `customer.address.zip` or `lines[2].qty`
+
+/// Split a path into its segments
+///
+/// `lines[2].qty` becomes `["lines", "[2]", "qty"]`. Dots inside brackets
+/// don't split, and neither do brackets inside quoted strings, so map keys
+/// like `headers["a.b"]` or `headers["a]b"]` stay whole.
+///
+/// Sequence diffs that line up two sides use `~` for the expected side: `[2]`
+/// is an index in actual, `~[3]` an index in expected, and `[2]~[3]` pairs
+/// the two up. Each of those is a single segment.
+pub fn segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut i = 0;
+    while i < path.len() {
+        let start = i;
+        let rest = &path[i..];
+        if rest.starts_with('.') {
+            i += 1;
+            continue;
+        } else if rest.starts_with('[') || rest.starts_with("~[") {
+            i = end_of_index(path, i);
+            if path[i..].starts_with("~[") {
+                i = end_of_index(path, i);
+            }
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let expected_index = rest[..end].ends_with('~') && rest[end..].starts_with('[');
+            i += if expected_index { end - 1 } else { end };
+        }
+        segments.push(&path[start..i]);
+    }
+    segments
+}
+
+/// The end of the `[...]` or `~[...]` at `start`, which may itself contain brackets
+///
+/// Brackets inside `"..."` or `'...'`, as map keys are written by `Debug`, are
+/// skipped over, along with backslash-escaped quotes.
+fn end_of_index(path: &str, start: usize) -> usize {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in path[start..].char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    path.len()
+}
+
+/// Put `child` underneath `parent`
+///
+/// `zip` under `address` becomes `address.zip`, `[2]` or `~[2]` under
+/// `lines` becomes `lines[2]` or `lines~[2]`, and an empty child or parent is
+/// left out.
+pub fn join(parent: &str, child: &str) -> String {
+    if child.is_empty() {
+        parent.to_string()
+    } else if parent.is_empty() || child.starts_with('[') || child.starts_with('~') {
+        format!("{}{}", parent, child)
+    } else {
+        format!("{}.{}", parent, child)
+    }
+}
+
+/// True if `path` is matched by the glob `pattern`, or is underneath a match
+///
+/// Patterns are paths whose segments can use `*` to match any characters
+/// within a single segment, e.g. `items[*].id` or `*_at`, and whole `**`
+/// segments to match any number of segments, e.g. `**.updated_at`.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    matches_prefix(&segments(pattern), &segments(path))
+}
+
+fn matches_prefix(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((&"**", rest)) => (0..path.len() + 1).any(|skip| matches_prefix(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) =>
+                matches_segment(first.as_bytes(), segment.as_bytes())
+                    && matches_prefix(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// Glob match a single segment, where `*` matches any run of characters
+fn matches_segment(pattern: &[u8], segment: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => segment.is_empty(),
+        Some((&b'*', rest)) =>
+            (0..segment.len() + 1).any(|skip| matches_segment(rest, &segment[skip..])),
+        Some((c, rest)) => segment.split_first()
+            .is_some_and(|(s, segment_rest)| c == s && matches_segment(rest, segment_rest)),
+    }
+}
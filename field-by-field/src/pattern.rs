@@ -0,0 +1,71 @@
+//! `assert_fields!`, for checking some fields of any value against a pattern
+
+/// Panics unless the fields listed in a struct pattern are equal
+///
+/// e.g. `assert_fields!(resp, Response { status: 200, body: Body { kind: "json", .. }, .. })`
+///
+/// Each field is either compared with `!=` to an expression, or is itself a
+/// pattern like `Body { .. }` for a nested struct. Only the listed fields are
+/// checked, and any that differ are reported with their path, like
+/// `body.kind`. The type doesn't need to implement anything, as long as its
+/// fields are visible. Without `..`, leaving a field out of the pattern is a
+/// compile error, just as it would be in a `let` or `match`.
+#[macro_export]
+macro_rules! assert_fields {
+    (@pattern $list:ident, $value:expr, $path:expr, $($ty:ident)::+ { $($fields:tt)* }) => {
+        $crate::assert_fields!(@fields $list, $value, $path, ($($ty)::+) [] $($fields)*);
+    };
+    (@pattern $list:ident, $value:expr, $path:expr, $expected:expr) => {{
+        let expected = $expected;
+        if $value != expected {
+            $list.push($crate::UnequalField {
+                field_name: ($path).to_string(),
+                actually: Box::new({
+                    #[allow(unused_imports)]
+                    use $crate::{ViaDebug, ViaPlaceholder};
+                    (&$crate::MaybeDebug(&$value)).rendered()
+                }),
+                expected: Box::new($crate::Rendered::new(&expected)),
+                kind: $crate::DiffKind::Changed,
+            });
+        }
+    }};
+
+    // Every field has been checked, so make sure the pattern names the right
+    // fields of the right type, and mentions all of them unless it has `..`
+    (@fields $list:ident, $value:expr, $path:expr, ($($ty:tt)*) [$($seen:ident)*]) => {
+        let $($ty)* { $($seen: _),* } = &$value;
+    };
+    (@fields $list:ident, $value:expr, $path:expr, ($($ty:tt)*) [$($seen:ident)*] ..) => {
+        let $($ty)* { $($seen: _,)* .. } = &$value;
+    };
+    (@fields $list:ident, $value:expr, $path:expr, $ty:tt [$($seen:ident)*]
+     $field:ident : $($inner_ty:ident)::+ { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        {
+            let path = $crate::path::join($path, stringify!($field));
+            $crate::assert_fields!(@pattern $list, $value.$field, &path,
+                                   $($inner_ty)::+ { $($inner)* });
+        }
+        $crate::assert_fields!(@fields $list, $value, $path, $ty [$($seen)* $field] $($($rest)*)?);
+    };
+    (@fields $list:ident, $value:expr, $path:expr, $ty:tt [$($seen:ident)*]
+     $field:ident : $expected:expr $(, $($rest:tt)*)?) => {
+        {
+            let path = $crate::path::join($path, stringify!($field));
+            $crate::assert_fields!(@pattern $list, $value.$field, &path, $expected);
+        }
+        $crate::assert_fields!(@fields $list, $value, $path, $ty [$($seen)* $field] $($($rest)*)?);
+    };
+
+    ($value:expr, $($pattern:tt)+) => {{
+        let mut list: Vec<$crate::UnequalField> = Vec::new();
+        let value = &$value;
+        $crate::assert_fields!(@pattern list, *value, "", $($pattern)+);
+        if !list.is_empty() {
+            let diff = $crate::Diff::from_fields(list);
+            #[allow(unused_imports)]
+            use $crate::{ViaDebug, ViaPlaceholder};
+            panic!("{}", $crate::mismatch_message(&diff, &(&$crate::MaybeDebug(value)).rendered()));
+        }
+    }};
+}
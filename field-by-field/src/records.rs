@@ -0,0 +1,100 @@
+//! Comparing sets of records by a key, regardless of their order
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use path;
+use {Diff, DiffKind, EqualFieldByField, Rendered, UnequalField};
+
+/// Match up records by key, and find the differences between each pair
+///
+/// Records are put under a `[key]` path, so a differing field looks like
+/// `[42].status: Paid != Pending`. Records whose key is only on one side are
+/// reported as unexpected or missing.
+///
+/// Panics if two records on the same side have the same key.
+pub fn records_diff_by_key<T, K, F>(actual: &[T], expected: &[T], key: F) -> Diff
+    where T: EqualFieldByField,
+          K: Hash + Eq + Debug,
+          F: Fn(&T) -> K
+{
+    let actual_by_key = index_by_key(actual, &key, "actual");
+    let expected_by_key = index_by_key(expected, &key, "expected");
+
+    let mut fields = Vec::new();
+    for record in expected {
+        let k = key(record);
+        let segment = format!("[{:?}]", k);
+        match actual_by_key.get(&k) {
+            Some(actual_record) => fields.extend(
+                actual_record.fields_not_equal(record)
+                    .into_iter()
+                    .map(|field| field.nested_under(&segment))),
+            None => fields.push(UnequalField {
+                field_name: segment,
+                actually: Box::new(Rendered::absent()),
+                expected: Box::new(record.debug_dump()),
+                kind: DiffKind::OnlyInExpected,
+            }),
+        }
+    }
+    for record in actual {
+        let k = key(record);
+        if !expected_by_key.contains_key(&k) {
+            fields.push(UnequalField {
+                field_name: format!("[{:?}]", k),
+                actually: Box::new(record.debug_dump()),
+                expected: Box::new(Rendered::absent()),
+                kind: DiffKind::OnlyInActual,
+            });
+        }
+    }
+    Diff::from_fields(fields)
+}
+
+/// Panics if the records don't match up by key, or if any pair differs
+///
+/// e.g. `assert_records_eq_by_key(&rows, &expected_rows, |row| row.id)`. See
+/// `records_diff_by_key` for how records are matched.
+pub fn assert_records_eq_by_key<T, K, F>(actual: &[T], expected: &[T], key: F)
+    where T: EqualFieldByField,
+          K: Hash + Eq + Debug,
+          F: Fn(&T) -> K
+{
+    let diff = records_diff_by_key(actual, expected, key);
+    if diff.is_empty() {
+        return;
+    }
+    let (mut missing, mut unexpected, mut changed) = (0, 0, Vec::new());
+    diff.walk(&mut |path, node| match *node {
+        Diff::Leaf { kind: DiffKind::OnlyInExpected, .. } => missing += 1,
+        Diff::Leaf { kind: DiffKind::OnlyInActual, .. } => unexpected += 1,
+        Diff::Leaf { kind: DiffKind::Changed, .. }
+        | Diff::Leaf { kind: DiffKind::Unmatched, .. } => {
+            let record = path::segments(path)[0].to_string();
+            if changed.last() != Some(&record) {
+                changed.push(record);
+            }
+        }
+        Diff::Node { .. } => {}
+    });
+    panic!("\n    Records are not equal:\n{}    {} missing, {} unexpected, {} changed \
+            (of {} actual and {} expected)\n",
+           diff.render(8), missing, unexpected, changed.len(), actual.len(), expected.len());
+}
+
+fn index_by_key<'a, T, K, F>(records: &'a [T], key: &F, side: &str) -> HashMap<K, &'a T>
+    where K: Hash + Eq + Debug,
+          F: Fn(&T) -> K
+{
+    let mut by_key = HashMap::with_capacity(records.len());
+    for record in records {
+        let k = key(record);
+        if by_key.contains_key(&k) {
+            panic!("More than one {} record has the key {:?}", side, k);
+        }
+        by_key.insert(k, record);
+    }
+    by_key
+}
@@ -0,0 +1,121 @@
+//! Helpers for controlling how values show up in failure messages
+
+use std::any;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hasher;
+
+/// Stands in for a value that must never be printed
+///
+/// Its `Debug` output is a short hash of the hidden value, so that you can
+/// still tell whether two redacted values are the same without seeing them:
+/// `<redacted, hash a1b2…>`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Redacted {
+    hash: u64,
+}
+
+impl Redacted {
+    pub fn new<T: Debug + ?Sized>(value: &T) -> Redacted {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(format!("{:?}", value).as_bytes());
+        Redacted { hash: hasher.finish() }
+    }
+}
+
+impl Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<redacted, hash {:04x}…>", self.hash >> 48)
+    }
+}
+
+/// The `Debug` output of a value, captured as a string
+///
+/// Useful when a value can't be cloned into a `Box<Debug>` as-is, for example
+/// because it borrows from something or needs to be redacted first.
+pub struct Rendered(String);
+
+impl Rendered {
+    pub fn new<T: Debug + ?Sized>(value: &T) -> Rendered {
+        Rendered(format!("{:?}", value))
+    }
+
+    /// Capture the `Display` output of a value instead
+    pub fn display<T: Display + ?Sized>(value: &T) -> Rendered {
+        Rendered(value.to_string())
+    }
+
+    /// Show a normalized value along with the raw value it came from
+    ///
+    /// e.g. `"bob@example.com" (normalized from "Bob@Example.com ")`
+    pub fn normalized<N, R>(normalized: &N, raw: &R) -> Rendered
+        where N: Debug + ?Sized,
+              R: Debug + ?Sized
+    {
+        Rendered(format!("{:?} (normalized from {:?})", normalized, raw))
+    }
+
+    /// Stands in for the missing side of an `OnlyInActual` or `OnlyInExpected` difference
+    pub fn absent() -> Rendered {
+        Rendered("<absent>".to_string())
+    }
+}
+
+impl Debug for Rendered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A value whose `Debug` implementation is the given closure
+///
+/// Created by `debug_with`.
+pub struct DebugWith<F>(F);
+
+/// Implement `Debug` with a closure
+///
+/// Used by the derive to print whole values when some fields are redacted.
+pub fn debug_with<F>(f: F) -> DebugWith<F>
+    where F: Fn(&mut fmt::Formatter) -> fmt::Result
+{
+    DebugWith(f)
+}
+
+impl<F> Debug for DebugWith<F>
+    where F: Fn(&mut fmt::Formatter) -> fmt::Result
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// Renders a value with `Debug` if it implements it, or a placeholder if not
+///
+/// This relies on autoref-based specialization: it must be called as
+/// `(&MaybeDebug(&value)).rendered()` with both `ViaDebug` and
+/// `ViaPlaceholder` in scope, and it only uses `Debug` when the compiler can
+/// see that the type implements it at the call site.
+#[doc(hidden)]
+pub struct MaybeDebug<'a, T: ?Sized + 'a>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaDebug {
+    fn rendered(&self) -> Rendered;
+}
+
+impl<'a, T: Debug + ?Sized> ViaDebug for MaybeDebug<'a, T> {
+    fn rendered(&self) -> Rendered {
+        Rendered::new(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaPlaceholder {
+    fn rendered(&self) -> Rendered;
+}
+
+impl<'a, 'b, T: ?Sized> ViaPlaceholder for &'b MaybeDebug<'a, T> {
+    fn rendered(&self) -> Rendered {
+        Rendered(format!("<value without Debug, type = {}>", any::type_name::<T>()))
+    }
+}
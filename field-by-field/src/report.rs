@@ -0,0 +1,91 @@
+//! Failure messages
+
+use std::fmt::Debug;
+
+use soft::SoftFailure;
+use Diff;
+
+/// The message that `assert_equal_field_by_field` panics with
+///
+/// ```text
+///     Items are not equal:
+///         a: 1 != 3
+///         d: 3 != 2
+///     actually: MyStruct { a: 1, b: 3, c: 3, d: 3, e: 3 }
+///     expected: MyStruct { a: 3, b: 3, c: 3, d: 2, e: 3 }
+/// ```
+pub fn not_equal_message(diff: &Diff, actually: &dyn Debug, expected: &dyn Debug) -> String {
+    labelled_not_equal_message(diff, ("actually", "expected"), actually, expected)
+}
+
+/// `not_equal_message`, with other labels than `actually` and `expected`
+///
+/// The labels are padded to the same width so that the values line up.
+pub fn labelled_not_equal_message(diff: &Diff,
+                                  (actually_label, expected_label): (&str, &str),
+                                  actually: &dyn Debug,
+                                  expected: &dyn Debug)
+-> String {
+    let width = actually_label.len().max(expected_label.len());
+    format!("\n    Items are not equal:\n{}    {:>width$}: {:?}\n    {:>width$}: {:?}\n",
+            diff.render(8), actually_label, actually, expected_label, expected, width = width)
+}
+
+/// The message that `SoftAsserter` panics with
+///
+/// ```text
+///     2 of 3 comparisons were not equal:
+///
+///     cart:
+///         total: 0 != 1
+///         actually: Cart { total: 0 }
+///         expected: Cart { total: 1 }
+///
+///     user:
+///         ...
+/// ```
+pub fn soft_failures_message(failures: &[SoftFailure], checked: usize) -> String {
+    let mut message = format!("\n    {} of {} comparisons were not equal:\n",
+                              failures.len(), checked);
+    for failure in failures {
+        message.push_str(&format!("\n    {}:\n{}        actually: {}\n        expected: {}\n",
+                                  failure.label, failure.diff.render(8),
+                                  failure.actually, failure.expected));
+    }
+    message
+}
+
+/// The message that a derived expectation's `assert_matches` panics with
+///
+/// ```text
+///     Item does not match expectation:
+///         id: "abc" does not match /^ord_/
+///     actually: Order { id: "abc", qty: 3 }
+/// ```
+pub fn mismatch_message(diff: &Diff, actually: &dyn Debug) -> String {
+    format!("\n    Item does not match expectation:\n{}    actually: {:?}\n",
+            diff.render(8), actually)
+}
+
+/// The message that `assert_changed_only` panics with
+///
+/// ```text
+///     Fields changed that weren't expected to:
+///         priority: 1 != 2
+///     Fields expected to change that didn't:
+///         updated_at
+/// ```
+pub fn changed_only_message(unexpected: &Diff, unchanged: &[&str]) -> String {
+    let mut message = String::from("\n");
+    if !unexpected.is_empty() {
+        message.push_str("    Fields changed that weren't expected to:\n");
+        message.push_str(&unexpected.render(8));
+    }
+    if !unchanged.is_empty() {
+        message.push_str("    Fields expected to change that didn't:\n");
+        for path in unchanged {
+            message.push_str(&format!("        {}\n", path));
+        }
+    }
+    message
+}
@@ -0,0 +1,122 @@
+//! Comparisons for sequences that aren't just element-by-element
+
+use std::fmt::{self, Debug};
+
+use render::Rendered;
+
+/// The elements that two sequences don't have in common, ignoring order
+///
+/// Elements are grouped with their multiplicities, so `[a, a, b]` vs `[a, b]`
+/// has one `a` only in actual.
+pub struct UnorderedDiff<'a, T: 'a> {
+    pub only_in_actual: Vec<(&'a T, usize)>,
+    pub only_in_expected: Vec<(&'a T, usize)>,
+}
+
+impl<'a, T: 'a> UnorderedDiff<'a, T> {
+    /// True if both sequences contain the same elements the same number of times
+    pub fn is_empty(&self) -> bool {
+        self.only_in_actual.is_empty() && self.only_in_expected.is_empty()
+    }
+
+    /// `actually_rendered`, rendering each element with `render`
+    pub fn actually_rendered_with<F: Fn(&T) -> Rendered>(&self, render: F) -> Rendered {
+        Rendered::new(&OnlyIn {
+            prefix: "only in actual: ",
+            elements: &self.only_in_actual,
+            render,
+        })
+    }
+
+    /// `expected_rendered`, rendering each element with `render`
+    pub fn expected_rendered_with<F: Fn(&T) -> Rendered>(&self, render: F) -> Rendered {
+        Rendered::new(&OnlyIn {
+            prefix: "only in expected: ",
+            elements: &self.only_in_expected,
+            render,
+        })
+    }
+
+    /// `missing_rendered`, rendering each element with `render`
+    pub fn missing_rendered_with<F: Fn(&T) -> Rendered>(&self, render: F) -> Rendered {
+        Rendered::new(&OnlyIn { prefix: "", elements: &self.only_in_expected, render })
+    }
+}
+
+impl<'a, T: Debug + 'a> UnorderedDiff<'a, T> {
+    /// The elements only in actual, formatted for an `UnequalField`
+    pub fn actually_rendered(&self) -> Rendered {
+        self.actually_rendered_with(Rendered::new)
+    }
+
+    /// The elements only in expected, formatted for an `UnequalField`
+    pub fn expected_rendered(&self) -> Rendered {
+        self.expected_rendered_with(Rendered::new)
+    }
+
+    /// Just the elements only in expected, for when the extra ones in actual don't matter
+    pub fn missing_rendered(&self) -> Rendered {
+        self.missing_rendered_with(Rendered::new)
+    }
+}
+
+/// Compare two sequences as multisets
+///
+/// Only needs `PartialEq`, so this is quadratic in the length of the inputs.
+pub fn unordered_diff<'a, T, A, E>(actual: A, expected: E) -> UnorderedDiff<'a, T>
+    where T: PartialEq + 'a,
+          A: IntoIterator<Item = &'a T>,
+          E: IntoIterator<Item = &'a T>
+{
+    let mut unmatched_expected: Vec<Option<&'a T>> = expected.into_iter().map(Some).collect();
+    let mut only_in_actual = Vec::new();
+    for item in actual {
+        let found = unmatched_expected.iter_mut()
+            .find(|e| **e == Some(item));
+        match found {
+            Some(slot) => *slot = None,
+            None => only_in_actual.push(item),
+        }
+    }
+    let only_in_expected = unmatched_expected.into_iter().flatten().collect();
+
+    UnorderedDiff {
+        only_in_actual: count_equal(only_in_actual),
+        only_in_expected: count_equal(only_in_expected),
+    }
+}
+
+/// Group equal items together, in order of first appearance
+fn count_equal<T: PartialEq>(items: Vec<&T>) -> Vec<(&T, usize)> {
+    let mut counts: Vec<(&T, usize)> = Vec::new();
+    for item in items {
+        match counts.iter_mut().find(|&&mut (seen, _)| seen == item) {
+            Some(&mut (_, ref mut count)) => *count += 1,
+            None => counts.push((item, 1)),
+        }
+    }
+    counts
+}
+
+/// Debug formats as `only in actual: ["a" ×2, "b"]`, with any prefix
+struct OnlyIn<'a, 'b: 'a, T: 'b, F> {
+    prefix: &'static str,
+    elements: &'a [(&'b T, usize)],
+    render: F,
+}
+
+impl<'a, 'b, T, F: Fn(&T) -> Rendered> Debug for OnlyIn<'a, 'b, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[", self.prefix)?;
+        for (i, &(element, count)) in self.elements.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{:?}", (self.render)(element))?;
+            if count > 1 {
+                write!(f, " ×{}", count)?;
+            }
+        }
+        f.write_str("]")
+    }
+}
@@ -0,0 +1,94 @@
+//! Soft assertions, which collect failures and panic once at the end
+
+use std::thread;
+
+use report::soft_failures_message;
+use {Diff, EqualFieldByField};
+
+/// One comparison in a `SoftAsserter` that wasn't equal
+#[derive(Debug)]
+pub struct SoftFailure {
+    /// Which comparison this was
+    pub label: String,
+    pub diff: Diff,
+    /// Both whole values, as shown by `EqualFieldByField::debug_dump`
+    pub actually: String,
+    pub expected: String,
+}
+
+/// Runs many comparisons, and then panics once with every difference
+///
+/// e.g. `soft.check("cart", &cart, &expected_cart)` for each comparison, then
+/// `soft.finish()` to panic if any of them weren't equal. If it's dropped
+/// without calling `finish`, it panics then instead, unless the thread is
+/// already panicking.
+#[derive(Debug, Default)]
+pub struct SoftAsserter {
+    checked: usize,
+    failures: Vec<SoftFailure>,
+}
+
+impl SoftAsserter {
+    pub fn new() -> SoftAsserter {
+        SoftAsserter::default()
+    }
+
+    /// Compare `actual` with `expected`, remembering any differences under `label`
+    ///
+    /// Returns true if they were equal.
+    pub fn check<L, T>(&mut self, label: L, actual: &T, expected: &T) -> bool
+        where L: Into<String>,
+              T: EqualFieldByField
+    {
+        self.checked += 1;
+        let fields = actual.fields_not_equal(expected);
+        if fields.is_empty() {
+            return true;
+        }
+        self.failures.push(SoftFailure {
+            label: label.into(),
+            diff: Diff::from_fields(fields),
+            actually: format!("{:?}", actual.debug_dump()),
+            expected: format!("{:?}", expected.debug_dump()),
+        });
+        false
+    }
+
+    /// The comparisons that have failed so far
+    pub fn failures(&self) -> &[SoftFailure] {
+        &self.failures
+    }
+
+    /// Panic with every difference found, if there were any
+    pub fn finish(self) {
+        self.panic_if_failed();
+    }
+
+    fn panic_if_failed(&self) {
+        if !self.failures.is_empty() {
+            panic!("{}", soft_failures_message(&self.failures, self.checked));
+        }
+    }
+}
+
+impl Drop for SoftAsserter {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.panic_if_failed();
+        }
+    }
+}
+
+/// Compare several pairs of values, and panic once with all of their differences
+///
+/// e.g. `assert_all!(cart, expected_cart; user, expected_user)`. Each
+/// comparison is labelled with the source of its `actual` expression, like
+/// `cart`, in the `SoftAsserter` report.
+#[macro_export]
+macro_rules! assert_all {
+    ($($actual:expr, $expected:expr);* $(;)*) => {{
+        let mut soft = $crate::SoftAsserter::new();
+        $(soft.check(stringify!($actual), &$actual, &$expected);)*
+        soft.finish();
+    }};
+}